@@ -0,0 +1,85 @@
+//! Competition lifecycle for [`AsyncRobot`]s.
+//!
+//! PROS runs each competition period in its own FreeRTOS task and deletes that task when
+//! the competition mode changes. Deleting a task never drops the futures running on it,
+//! so instead of running robot code on those tasks, [`async_robot!`](crate::async_robot!)
+//! starts a single long-lived task that watches [`competition::mode`] and runs the future
+//! for the current period itself.
+//!
+//! When the mode changes, the running period future and every task spawned on the executor
+//! are dropped, whatever they are waiting on, and then [`AsyncRobot::on_mode_change`] is run before the next period starts.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use pros_core::{
+    competition::{self, CompetitionMode},
+    task::Builder,
+};
+
+use crate::{executor::EXECUTOR, AsyncRobot};
+
+/// Set by PROS calling `competition_initialize`, and cleared once `comp_init` has been started.
+static COMP_INIT_PENDING: AtomicBool = AtomicBool::new(false);
+
+/// Requests that [`AsyncRobot::comp_init`] runs at the start of the next disabled period.
+#[doc(hidden)]
+pub fn __request_comp_init() {
+    COMP_INIT_PENDING.store(true, Ordering::Release);
+}
+
+/// Spawns the task that runs the competition lifecycle for the robot returned by `robot`.
+#[doc(hidden)]
+pub fn __spawn_competition_runtime<R: AsyncRobot + 'static>(robot: fn() -> &'static mut R) {
+    Builder::new()
+        .name("pros-rs competition runtime")
+        .spawn(move || run(robot()))
+        .expect("Failed to spawn competition runtime task");
+}
+
+fn run<R: AsyncRobot>(robot: &mut R) -> ! {
+    EXECUTOR.with(|executor| {
+        let mut mode = competition::mode();
+
+        loop {
+            let comp_init = mode == CompetitionMode::Disabled
+                && COMP_INIT_PENDING.swap(false, Ordering::AcqRel);
+
+            let mut interrupted = || {
+                competition::mode() != mode
+                    || (mode == CompetitionMode::Disabled
+                        && COMP_INIT_PENDING.load(Ordering::Acquire))
+            };
+
+            let period = async {
+                if comp_init {
+                    robot.comp_init().await?;
+                }
+                match mode {
+                    CompetitionMode::Disabled => robot.disabled().await,
+                    CompetitionMode::Autonomous => robot.auto().await,
+                    CompetitionMode::Opcontrol => robot.opcontrol().await,
+                }
+            };
+
+            if let Some(result) = executor.block_on_until(period, &mut interrupted) {
+                result.unwrap();
+
+                // Tasks spawned by the period may outlive it, so keep running them until the mode changes.
+                executor.block_on_until(core::future::pending::<()>(), &mut interrupted);
+            }
+
+            executor.cancel_all();
+
+            let previous = mode;
+            mode = competition::mode();
+
+            if mode != previous {
+                executor
+                    .block_on_until(robot.on_mode_change(previous, mode), || false)
+                    .unwrap()
+                    .unwrap();
+                executor.cancel_all();
+            }
+        }
+    })
+}
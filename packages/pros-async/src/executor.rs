@@ -1,48 +1,136 @@
-use alloc::{collections::VecDeque, sync::Arc};
+use alloc::{
+    collections::VecDeque,
+    sync::{Arc, Weak},
+    vec::Vec,
+};
 use core::{
     cell::RefCell,
     future::Future,
-    pin::Pin,
+    pin::pin,
     sync::atomic::{AtomicBool, Ordering},
-    task::{Context, Poll},
+    task::{Context, Poll, Waker},
     time::Duration,
 };
 
 use async_task::{Runnable, Task};
-use pros_core::{os_task_local, task::delay};
+use pros_core::{
+    os_task_local,
+    sync::Mutex,
+    task::{self, Builder, TaskHandle},
+};
+use spin::Once;
 use waker_fn::waker_fn;
 
 use super::reactor::Reactor;
+use crate::metrics::{ExecutorMetrics, Instrumented, TaskStats};
 
 os_task_local! {
     pub(crate) static EXECUTOR: Executor = Executor::new();
 }
 
+/// The queue of the dedicated executor task that [`spawn_on`] runs futures on.
+static SEND_QUEUE: Once<Arc<RunQueue>> = Once::new();
+
+/// How often [`Executor::block_on_until`] checks if it has been interrupted while idle.
+const INTERRUPT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How often devices are checked while futures are waiting for them to be ready.
+const IO_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// A queue of runnables that can be scheduled from any task.
+///
+/// Runnables are only ever run by the task that owns the queue,
+/// which is sent a notification whenever a runnable is pushed.
+pub(crate) struct RunQueue {
+    runnables: Mutex<VecDeque<Runnable>>,
+    owner: Once<TaskHandle>,
+}
+
+impl RunQueue {
+    fn new() -> Self {
+        Self {
+            runnables: Mutex::new(VecDeque::new()),
+            owner: Once::new(),
+        }
+    }
+
+    fn push(&self, runnable: Runnable) {
+        self.runnables.lock().unwrap().push_back(runnable);
+
+        if let Some(owner) = self.owner.get() {
+            owner.notify();
+        }
+    }
+
+    fn pop(&self) -> Option<Runnable> {
+        self.runnables.lock().unwrap().pop_front()
+    }
+
+    fn len(&self) -> usize {
+        self.runnables.lock().unwrap().len()
+    }
+}
+
+/// Creates a waker that sets `woken` and notifies `task`.
+///
+/// Unlike the wakers of tasks spawned on an executor, this waker can be woken from any FreeRTOS task.
+pub(crate) fn notify_waker(task: TaskHandle, woken: Arc<AtomicBool>) -> Waker {
+    waker_fn(move || {
+        woken.store(true, Ordering::Release);
+        task.notify();
+    })
+}
+
+/// A task that was spawned on an executor and may not have finished yet.
+struct Spawned {
+    /// The statistics of the task's future, which are dropped along with the future.
+    stats: Weak<TaskStats>,
+    /// Schedules the task, even if it is waiting on something other than the reactor.
+    waker: Waker,
+}
+
 pub(crate) struct Executor {
-    queue: RefCell<VecDeque<Runnable>>,
+    queue: Arc<RunQueue>,
     pub(crate) reactor: RefCell<Reactor>,
+    tasks: RefCell<Vec<Weak<TaskStats>>>,
+    spawned: RefCell<Vec<Spawned>>,
 }
 
 impl !Send for Executor {}
 impl !Sync for Executor {}
 
 impl Executor {
-    pub const fn new() -> Self {
+    pub fn new() -> Self {
+        let queue = RunQueue::new();
+        queue.owner.call_once(task::current);
+
         Self {
-            queue: RefCell::new(VecDeque::new()),
+            queue: Arc::new(queue),
             reactor: RefCell::new(Reactor::new()),
+            tasks: RefCell::new(Vec::new()),
+            spawned: RefCell::new(Vec::new()),
         }
     }
 
-    pub fn spawn<T>(&'static self, future: impl Future<Output = T> + 'static) -> Task<T> {
-        // SAFETY: `runnable` will never be moved off this thread or shared with another thread because of the `!Send + !Sync` bounds on `Self`.
-        //         Both `future` and `schedule` are `'static` so they cannot be used after being freed.
-        //   TODO: Make sure that the waker can never be sent off the thread.
-        let (runnable, task) = unsafe {
-            async_task::spawn_unchecked(future, |runnable| {
-                self.queue.borrow_mut().push_back(runnable)
-            })
-        };
+    pub fn spawn<T>(&self, future: impl Future<Output = T> + 'static) -> Task<T> {
+        // SAFETY: `runnable` is only ever run on the task that owns this executor, since runnables are only
+        //         popped from the queue in `tick` and `cancel_all`, and `Self` is `!Send + !Sync`.
+        //         The schedule function may be called from any task, which is fine because it only pushes to a `RunQueue`.
+        //         Both `future` and `schedule` are `'static` so they cannot be used after being freed,
+        //         even if this executor is dropped when its task exits.
+        let future = Instrumented::new(future);
+        let stats = future.stats();
+        let queue = self.queue.clone();
+        let (runnable, task) =
+            unsafe { async_task::spawn_unchecked(future, move |runnable| queue.push(runnable)) };
+
+        let mut spawned = self.spawned.borrow_mut();
+        spawned.retain(|task| task.stats.strong_count() > 0);
+        spawned.push(Spawned {
+            stats,
+            waker: runnable.waker(),
+        });
+        drop(spawned);
 
         runnable.schedule();
 
@@ -52,11 +140,7 @@ impl Executor {
     pub(crate) fn tick(&self) -> bool {
         self.reactor.borrow_mut().tick();
 
-        let runnable = {
-            let mut queue = self.queue.borrow_mut();
-            queue.pop_front()
-        };
-        match runnable {
+        match self.queue.pop() {
             Some(runnable) => {
                 runnable.run();
                 true
@@ -65,27 +149,160 @@ impl Executor {
         }
     }
 
-    pub fn block_on<R>(&self, mut task: Task<R>) -> R {
-        let woken = Arc::new(AtomicBool::new(true));
+    /// Blocks the current task until it receives a notification or the next sleeper is due,
+    /// waiting for no longer than `max`.
+    fn park(&self, max: Option<Duration>) {
+        let mut timeout = max.map_or(pros_sys::TIMEOUT_MAX, |max| max.as_millis() as u32);
 
-        let waker = waker_fn({
-            let woken = woken.clone();
-            move || woken.store(true, Ordering::Relaxed)
-        });
+        let reactor = self.reactor.borrow();
+        if let Some(target) = reactor.sleepers.next_target() {
+            let until_due = target.saturating_sub(unsafe { pros_sys::millis() }) + 1;
+            timeout = timeout.min(until_due);
+        }
+        if !reactor.io.is_empty() {
+            timeout = timeout.min(IO_POLL_INTERVAL.as_millis() as u32);
+        }
+        drop(reactor);
+
+        unsafe {
+            pros_sys::task_notify_take(true, timeout);
+        }
+    }
+
+    fn run_until<F: Future>(
+        &self,
+        future: F,
+        mut interrupted: impl FnMut() -> bool,
+        max_park: Option<Duration>,
+    ) -> Option<F::Output> {
+        let woken = Arc::new(AtomicBool::new(true));
+        let waker = notify_waker(task::current(), woken.clone());
         let mut cx = Context::from_waker(&waker);
+        let mut future = pin!(Instrumented::new(future));
 
         loop {
-            if woken.swap(false, Ordering::Relaxed) {
-                if let Poll::Ready(output) = Pin::new(&mut task).poll(&mut cx) {
-                    return output;
+            if interrupted() {
+                return None;
+            }
+
+            if woken.swap(false, Ordering::Acquire) {
+                if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                    return Some(output);
                 }
-                self.tick();
-                // there might be another future to poll, so we continue without sleeping
-                continue;
             }
 
-            delay(Duration::from_millis(10));
-            self.tick();
+            // there might be another future to poll, so we only sleep if nothing was run
+            if !self.tick() && !woken.load(Ordering::Acquire) {
+                self.park(max_park);
+            }
+        }
+    }
+
+    pub fn block_on<R>(&self, task: Task<R>) -> R {
+        self.run_until(task, || false, None).unwrap()
+    }
+
+    /// Polls `future` until it completes, ticking the executor in between polls.
+    ///
+    /// Unlike [`Executor::block_on`], the future is polled in place rather than spawned,
+    /// so it may borrow from the caller. If `interrupted` returns true before the future
+    /// completes, the future is dropped and `None` is returned.
+    pub fn block_on_until<F: Future>(
+        &self,
+        future: F,
+        interrupted: impl FnMut() -> bool,
+    ) -> Option<F::Output> {
+        self.run_until(future, interrupted, Some(INTERRUPT_POLL_INTERVAL))
+    }
+
+    /// Runs this executor and every runnable scheduled on `shared` forever.
+    fn run_forever(&self, shared: &RunQueue) -> ! {
+        shared.owner.call_once(task::current);
+
+        loop {
+            let ran_local = self.tick();
+            let ran_shared = match shared.pop() {
+                Some(runnable) => {
+                    runnable.run();
+                    true
+                }
+                None => false,
+            };
+
+            if !ran_local && !ran_shared {
+                self.park(None);
+            }
         }
     }
+
+    /// Starts tracking the statistics of a task that is polled by this executor.
+    pub(crate) fn register(&self, stats: &Arc<TaskStats>) {
+        let mut tasks = self.tasks.borrow_mut();
+        tasks.retain(|task| task.strong_count() > 0);
+        tasks.push(Arc::downgrade(stats));
+    }
+
+    pub(crate) fn metrics(&self) -> ExecutorMetrics {
+        ExecutorMetrics {
+            queued: self.queue.len(),
+            timers: self.reactor.borrow().sleepers.len(),
+            tasks: self
+                .tasks
+                .borrow()
+                .iter()
+                .filter_map(Weak::upgrade)
+                .map(|stats| stats.snapshot())
+                .collect(),
+        }
+    }
+
+    /// Drops every task that has been spawned on this executor.
+    ///
+    /// Every unfinished task is woken so that it is scheduled, including tasks that are waiting on
+    /// something other than the reactor (such as an async [`Mutex`](crate::sync::Mutex)), and then every
+    /// scheduled task is dropped without being run. Dropping a task may cause other tasks to be spawned or
+    /// scheduled, so this repeats until the queue is empty.
+    pub(crate) fn cancel_all(&self) {
+        self.reactor.borrow_mut().wake_all();
+
+        loop {
+            let spawned = core::mem::take(&mut *self.spawned.borrow_mut());
+            for task in spawned {
+                task.waker.wake();
+            }
+
+            let Some(runnable) = self.queue.pop() else {
+                break;
+            };
+            drop(runnable);
+        }
+    }
+}
+
+/// Spawns a `Send` future on the dedicated executor task, starting the task if it isn't running yet.
+pub(crate) fn spawn_on<T: Send + 'static>(
+    future: impl Future<Output = T> + Send + 'static,
+) -> Task<T> {
+    let queue = SEND_QUEUE
+        .call_once(|| {
+            let queue = Arc::new(RunQueue::new());
+
+            Builder::new()
+                .name("pros-rs executor")
+                .spawn({
+                    let queue = queue.clone();
+                    move || EXECUTOR.with(|executor| executor.run_forever(&queue))
+                })
+                .expect("Failed to spawn executor task");
+
+            queue
+        })
+        .clone();
+
+    let future = Instrumented::new(future);
+    let (runnable, task) = async_task::spawn(future, move |runnable| queue.push(runnable));
+
+    runnable.schedule();
+
+    task
 }
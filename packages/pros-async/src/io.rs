@@ -0,0 +1,376 @@
+//! Asynchronous byte streams.
+//!
+//! [`AsyncRead`] and [`AsyncWrite`] are the async equivalents of [`Read`](pros_core::io::Read) and
+//! [`Write`](pros_core::io::Write). They are implemented for [`Stdin`] and, with the `devices` feature,
+//! for VEXLink radios (`RxLink` and `TxLink`) and `SerialPort`s. When no data is available to read,
+//! or there is no room to write, the future waits on the executor's reactor until the device is ready instead of returning zero or
+//! blocking the task, so other futures can run in the meantime.
+//!
+//! The [`AsyncReadExt`] and [`AsyncWriteExt`] traits provide `async fn`-style helpers on top of them.
+//! Their methods have the same names as the blocking ones, so if the blocking traits are also in scope
+//! (for example through the `pros` prelude), call them as `AsyncReadExt::read(&mut serial, &mut buf)`.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut serial = SerialPort::open(peripherals.port_1, 115200)?;
+//! let mut buf = [0; 64];
+//! loop {
+//!     let read = serial.read(&mut buf).await?;
+//!     serial.write_all(&buf[..read]).await?;
+//! }
+//! ```
+
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use pros_core::io::{self, Stdin};
+#[cfg(feature = "devices")]
+use pros_devices::smart::{
+    link::{RxLink, TxLink},
+    serial::SerialPort,
+    SmartDevice,
+};
+
+use crate::executor::EXECUTOR;
+
+/// Reads bytes asynchronously.
+pub trait AsyncRead {
+    /// Attempts to read bytes into `buf`.
+    ///
+    /// Returns the number of bytes read, or [`Poll::Pending`] if no bytes are available,
+    /// in which case the task is woken once they are.
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>>;
+}
+
+/// Writes bytes asynchronously.
+pub trait AsyncWrite {
+    /// Attempts to write bytes from `buf`.
+    ///
+    /// Returns the number of bytes written, or [`Poll::Pending`] if there is no room to write,
+    /// in which case the task is woken once there is.
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>>;
+
+    /// Attempts to flush any buffered data.
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>>;
+}
+
+/// Registers the current task to be woken by the reactor once `ready` returns `true`.
+fn wait_until(cx: &Context<'_>, ready: impl FnMut() -> bool + 'static) {
+    EXECUTOR.with(|e| e.reactor.borrow_mut().io.push(cx.waker().clone(), ready));
+}
+
+#[cfg(feature = "devices")]
+impl AsyncRead for RxLink {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let available = self
+            .num_incoming_bytes()
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "failed to read from link"))?;
+        if available == 0 {
+            let port = self.port_index();
+            wait_until(cx, move || unsafe {
+                pros_sys::link_raw_receivable_size(port) != 0
+            });
+            return Poll::Pending;
+        }
+
+        let len = buf.len().min(available as usize);
+        let read = self
+            .receive(&mut buf[..len])
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "failed to read from link"))?;
+        Poll::Ready(Ok(read as usize))
+    }
+}
+
+#[cfg(feature = "devices")]
+impl AsyncWrite for TxLink {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let port = self.port_index();
+        let free = unsafe { pros_sys::link_raw_transmittable_size(port) };
+        if free == 0 {
+            wait_until(cx, move || unsafe {
+                pros_sys::link_raw_transmittable_size(port) != 0
+            });
+            return Poll::Pending;
+        }
+
+        let len = buf.len().min(free as usize);
+        let written = self
+            .transmit(&buf[..len])
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "failed to write to link"))?;
+        Poll::Ready(Ok(written as usize))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "devices")]
+impl AsyncRead for SerialPort {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let read = self
+            .try_read(buf)
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "failed to read from serial port"))?;
+        if read == 0 {
+            let port = self.port_index();
+            wait_until(cx, move || unsafe {
+                pros_sys::serial_get_read_avail(port) != 0
+            });
+            return Poll::Pending;
+        }
+
+        Poll::Ready(Ok(read))
+    }
+}
+
+#[cfg(feature = "devices")]
+impl AsyncWrite for SerialPort {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let written = self
+            .try_write(buf)
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "failed to write to serial port"))?;
+        if written == 0 {
+            let port = self.port_index();
+            wait_until(cx, move || unsafe {
+                pros_sys::serial_get_write_free(port) != 0
+            });
+            return Poll::Pending;
+        }
+
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncRead for Stdin {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let read = self.try_read(buf)?;
+        if read == 0 {
+            let stdin = *self;
+            wait_until(cx, move || stdin.bytes_available() != 0);
+            return Poll::Pending;
+        }
+
+        Poll::Ready(Ok(read))
+    }
+}
+
+/// Async helper methods for [`AsyncRead`] types.
+pub trait AsyncReadExt: AsyncRead {
+    /// Reads some bytes into `buf`, waiting until at least one byte is available.
+    ///
+    /// Returns the number of bytes read.
+    fn read<'a>(&'a mut self, buf: &'a mut [u8]) -> ReadFuture<'a, Self>
+    where
+        Self: Unpin,
+    {
+        ReadFuture { reader: self, buf }
+    }
+
+    /// Reads exactly enough bytes to fill `buf`.
+    fn read_exact<'a>(&'a mut self, buf: &'a mut [u8]) -> ReadExactFuture<'a, Self>
+    where
+        Self: Unpin,
+    {
+        ReadExactFuture { reader: self, buf }
+    }
+}
+
+impl<R: AsyncRead + ?Sized> AsyncReadExt for R {}
+
+/// Async helper methods for [`AsyncWrite`] types.
+pub trait AsyncWriteExt: AsyncWrite {
+    /// Writes some bytes from `buf`, waiting until there is room for at least one byte.
+    ///
+    /// Returns the number of bytes written.
+    fn write<'a>(&'a mut self, buf: &'a [u8]) -> WriteFuture<'a, Self>
+    where
+        Self: Unpin,
+    {
+        WriteFuture { writer: self, buf }
+    }
+
+    /// Writes all of `buf`.
+    fn write_all<'a>(&'a mut self, buf: &'a [u8]) -> WriteAllFuture<'a, Self>
+    where
+        Self: Unpin,
+    {
+        WriteAllFuture { writer: self, buf }
+    }
+
+    /// Flushes any buffered data.
+    fn flush(&mut self) -> FlushFuture<'_, Self>
+    where
+        Self: Unpin,
+    {
+        FlushFuture { writer: self }
+    }
+}
+
+impl<W: AsyncWrite + ?Sized> AsyncWriteExt for W {}
+
+/// A future returned by [`AsyncReadExt::read`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ReadFuture<'a, R: ?Sized> {
+    reader: &'a mut R,
+    buf: &'a mut [u8],
+}
+
+impl<R: AsyncRead + Unpin + ?Sized> Future for ReadFuture<'_, R> {
+    type Output = io::Result<usize>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        Pin::new(&mut *this.reader).poll_read(cx, this.buf)
+    }
+}
+
+/// A future returned by [`AsyncReadExt::read_exact`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ReadExactFuture<'a, R: ?Sized> {
+    reader: &'a mut R,
+    buf: &'a mut [u8],
+}
+
+impl<R: AsyncRead + Unpin + ?Sized> Future for ReadExactFuture<'_, R> {
+    type Output = io::Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        while !this.buf.is_empty() {
+            let read = match Pin::new(&mut *this.reader).poll_read(cx, this.buf) {
+                Poll::Ready(Ok(read)) => read,
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            };
+            if read == 0 {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "failed to fill whole buffer",
+                )));
+            }
+            this.buf = &mut core::mem::take(&mut this.buf)[read..];
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// A future returned by [`AsyncWriteExt::write`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WriteFuture<'a, W: ?Sized> {
+    writer: &'a mut W,
+    buf: &'a [u8],
+}
+
+impl<W: AsyncWrite + Unpin + ?Sized> Future for WriteFuture<'_, W> {
+    type Output = io::Result<usize>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        Pin::new(&mut *this.writer).poll_write(cx, this.buf)
+    }
+}
+
+/// A future returned by [`AsyncWriteExt::write_all`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WriteAllFuture<'a, W: ?Sized> {
+    writer: &'a mut W,
+    buf: &'a [u8],
+}
+
+impl<W: AsyncWrite + Unpin + ?Sized> Future for WriteAllFuture<'_, W> {
+    type Output = io::Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        while !this.buf.is_empty() {
+            let written = match Pin::new(&mut *this.writer).poll_write(cx, this.buf) {
+                Poll::Ready(Ok(written)) => written,
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            };
+            if written == 0 {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "failed to write whole buffer",
+                )));
+            }
+            this.buf = &this.buf[written..];
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// A future returned by [`AsyncWriteExt::flush`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct FlushFuture<'a, W: ?Sized> {
+    writer: &'a mut W,
+}
+
+impl<W: AsyncWrite + Unpin + ?Sized> Future for FlushFuture<'_, W> {
+    type Output = io::Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut *self.get_mut().writer).poll_flush(cx)
+    }
+}
@@ -3,6 +3,11 @@
 //! It has a reactor to improve the performance of some futures.
 //! It is recommended to use the `AsyncRobot` trait to run robot code.
 //! FreeRTOS tasks can still be used, but it is recommended to use only async tasks for performance.
+//!
+//! When using [`async_robot!`], robot code is cancelled when the competition mode changes.
+//! The future for the previous period and every task it spawned are dropped before the next period starts,
+//! even if they are waiting on something like an async [`Mutex`](sync::Mutex). Futures spawned with [`spawn_on`]
+//! run on a separate executor task, so they are not cancelled.
 
 #![no_std]
 #![feature(negative_impls)]
@@ -12,11 +17,17 @@ extern crate alloc;
 use core::{future::Future, task::Poll};
 
 use async_task::Task;
+#[doc(hidden)]
+pub use competition::{__request_comp_init, __spawn_competition_runtime};
 use executor::EXECUTOR;
-use pros_core::error::Result;
+use pros_core::{competition::CompetitionMode, error::Result};
 
+mod competition;
 mod executor;
+pub mod io;
+pub mod metrics;
 mod reactor;
+pub mod sync;
 
 /// Runs a future in the background without having to await it
 /// To get the the return value you can await a task.
@@ -24,6 +35,20 @@ pub fn spawn<T>(future: impl Future<Output = T> + 'static) -> Task<T> {
     executor::EXECUTOR.with(|e| e.spawn(future))
 }
 
+/// Runs a future in the background on a dedicated executor task instead of the current task.
+///
+/// The executor task is started the first time this function is called, and is shared by every
+/// future spawned with this function. Because the future may run on a different FreeRTOS task,
+/// it must be [`Send`]. The returned task can be awaited from any task.
+///
+/// Wakers given to futures in pros-rs can be woken from any FreeRTOS task, so a blocking task
+/// (such as one polling a sensor) can wake a future without it needing to poll in a loop.
+/// Executors wait for a task notification while idle, so sending other notifications to a task
+/// that is running an executor may cause it to wake up early.
+pub fn spawn_on<T: Send + 'static>(future: impl Future<Output = T> + Send + 'static) -> Task<T> {
+    executor::spawn_on(future)
+}
+
 /// Blocks the current task untill a return value can be extracted from the provided future.
 /// Does not poll all futures to completion.
 pub fn block_on<F: Future + 'static>(future: F) -> F::Output {
@@ -85,6 +110,19 @@ pub trait AsyncRobot {
     fn comp_init(&mut self) -> impl Future<Output = Result> {
         async { Ok(()) }
     }
+    /// Runs when the competition mode changes, after the future for the previous period
+    /// and every task it spawned have been dropped, but before the next period starts.
+    ///
+    /// This is a good place to put the robot into a safe state, such as by stopping all motors.
+    /// Unlike the other functions in this trait, this future is never cancelled.
+    fn on_mode_change(
+        &mut self,
+        from: CompetitionMode,
+        to: CompetitionMode,
+    ) -> impl Future<Output = Result> {
+        _ = (from, to);
+        async { Ok(()) }
+    }
 }
 
 #[doc(hidden)]
@@ -93,48 +131,34 @@ macro_rules! __gen_async_exports {
     ($rbt:ty) => {
         pub static mut ROBOT: Option<$rbt> = None;
 
+        // Robot code is run by the competition runtime task rather than the tasks PROS creates
+        // for each period, so these only need to exist for PROS to call.
+
         #[doc(hidden)]
         #[no_mangle]
-        extern "C" fn opcontrol() {
-            $crate::block_on(<$rbt as $crate::AsyncRobot>::opcontrol(unsafe {
-                ROBOT
-                    .as_mut()
-                    .expect("Expected initialize to run before opcontrol")
-            }))
-            .unwrap();
-        }
+        extern "C" fn opcontrol() {}
 
         #[doc(hidden)]
         #[no_mangle]
-        extern "C" fn autonomous() {
-            $crate::block_on(<$rbt as $crate::AsyncRobot>::auto(unsafe {
-                ROBOT
-                    .as_mut()
-                    .expect("Expected initialize to run before auto")
-            }))
-            .unwrap();
-        }
+        extern "C" fn autonomous() {}
 
         #[doc(hidden)]
         #[no_mangle]
-        extern "C" fn disabled() {
-            $crate::block_on(<$rbt as $crate::AsyncRobot>::disabled(unsafe {
-                ROBOT
-                    .as_mut()
-                    .expect("Expected initialize to run before disabled")
-            }))
-            .unwrap();
-        }
+        extern "C" fn disabled() {}
 
         #[doc(hidden)]
         #[no_mangle]
         extern "C" fn competition_initialize() {
-            $crate::block_on(<$rbt as $crate::AsyncRobot>::comp_init(unsafe {
+            $crate::__request_comp_init();
+        }
+
+        #[doc(hidden)]
+        fn __start_competition_runtime() {
+            $crate::__spawn_competition_runtime::<$rbt>(|| unsafe {
                 ROBOT
                     .as_mut()
-                    .expect("Expected initialize to run before comp_init")
-            }))
-            .unwrap();
+                    .expect("Expected initialize to run before the competition runtime")
+            });
         }
     };
 }
@@ -189,6 +213,7 @@ macro_rules! async_robot {
             unsafe {
                 ROBOT = Some(robot);
             }
+            __start_competition_runtime();
         }
     };
     ($rbt:ty, $init:expr) => {
@@ -200,6 +225,7 @@ macro_rules! async_robot {
             unsafe {
                 ROBOT = Some(robot);
             }
+            __start_competition_runtime();
         }
     };
 }
@@ -0,0 +1,186 @@
+//! Runtime metrics for the async executor.
+//!
+//! Every task spawned with [`spawn`](crate::spawn) or [`spawn_on`](crate::spawn_on), as well as
+//! every future passed to [`block_on`](crate::block_on), keeps track of how often and for how long it is polled.
+//! Futures should never block, so a single poll that takes longer than the [poll budget](set_poll_budget)
+//! prints a warning to stderr. A snapshot of the executor on the current FreeRTOS task can be taken with
+//! [`executor_metrics`], which is useful for rendering a debug screen.
+
+use alloc::{
+    sync::{Arc, Weak},
+    vec::Vec,
+};
+use core::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicU32, AtomicU64, Ordering},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use pros_core::eprintln;
+
+use crate::executor::EXECUTOR;
+
+/// The poll budget that is used if [`set_poll_budget`] is never called.
+pub const DEFAULT_POLL_BUDGET: Duration = Duration::from_millis(5);
+
+/// The poll budget in microseconds, or zero if slow polls should not be reported.
+static POLL_BUDGET: AtomicU64 = AtomicU64::new(DEFAULT_POLL_BUDGET.as_micros() as u64);
+
+static NEXT_TASK_ID: AtomicU32 = AtomicU32::new(0);
+
+/// Sets the longest time a single poll of a future may take before a warning is printed.
+///
+/// Passing `None` disables the warning. Slow polls are still counted in [`TaskMetrics::slow_polls`].
+pub fn set_poll_budget(budget: Option<Duration>) {
+    let micros = budget.map_or(0, |budget| (budget.as_micros() as u64).max(1));
+    POLL_BUDGET.store(micros, Ordering::Relaxed);
+}
+
+/// Returns the longest time a single poll of a future may take before a warning is printed.
+pub fn poll_budget() -> Option<Duration> {
+    match POLL_BUDGET.load(Ordering::Relaxed) {
+        0 => None,
+        micros => Some(Duration::from_micros(micros)),
+    }
+}
+
+/// Returns a snapshot of the metrics of the executor running on the current FreeRTOS task.
+///
+/// Tasks that have not been polled yet, or that have completed or been dropped, are not included.
+pub fn executor_metrics() -> ExecutorMetrics {
+    EXECUTOR.with(|executor| executor.metrics())
+}
+
+/// A snapshot of the state of an executor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutorMetrics {
+    /// The number of tasks that are scheduled to be polled.
+    pub queued: usize,
+    /// The number of timers registered with the reactor, such as those created by [`sleep`](crate::sleep).
+    pub timers: usize,
+    /// The metrics of every live task on the executor, in the order they were first polled.
+    pub tasks: Vec<TaskMetrics>,
+}
+
+/// A snapshot of the metrics of a single task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskMetrics {
+    /// A unique identifier for the task.
+    pub id: u32,
+    /// The number of times the task has been polled.
+    pub polls: u32,
+    /// The total time spent polling the task.
+    pub poll_time: Duration,
+    /// The longest time a single poll of the task took.
+    pub max_poll_time: Duration,
+    /// The number of polls that took longer than the poll budget.
+    pub slow_polls: u32,
+}
+
+impl TaskMetrics {
+    /// Returns the average time a single poll of the task took.
+    pub fn mean_poll_time(&self) -> Duration {
+        match self.polls {
+            0 => Duration::ZERO,
+            polls => self.poll_time / polls,
+        }
+    }
+}
+
+/// Poll statistics shared between an [`Instrumented`] future and the executor it runs on.
+#[derive(Debug)]
+pub(crate) struct TaskStats {
+    id: u32,
+    polls: AtomicU32,
+    poll_time: AtomicU64,
+    max_poll_time: AtomicU64,
+    slow_polls: AtomicU32,
+}
+
+impl TaskStats {
+    fn new() -> Self {
+        Self {
+            id: NEXT_TASK_ID.fetch_add(1, Ordering::Relaxed),
+            polls: AtomicU32::new(0),
+            poll_time: AtomicU64::new(0),
+            max_poll_time: AtomicU64::new(0),
+            slow_polls: AtomicU32::new(0),
+        }
+    }
+
+    fn record(&self, elapsed: u64) {
+        self.polls.fetch_add(1, Ordering::Relaxed);
+        self.poll_time.fetch_add(elapsed, Ordering::Relaxed);
+        self.max_poll_time.fetch_max(elapsed, Ordering::Relaxed);
+
+        let budget = POLL_BUDGET.load(Ordering::Relaxed);
+        if budget != 0 && elapsed > budget {
+            self.slow_polls.fetch_add(1, Ordering::Relaxed);
+            eprintln!(
+                "pros-async: task {} blocked the executor for {:?} (budget is {:?})",
+                self.id,
+                Duration::from_micros(elapsed),
+                Duration::from_micros(budget),
+            );
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> TaskMetrics {
+        TaskMetrics {
+            id: self.id,
+            polls: self.polls.load(Ordering::Relaxed),
+            poll_time: Duration::from_micros(self.poll_time.load(Ordering::Relaxed)),
+            max_poll_time: Duration::from_micros(self.max_poll_time.load(Ordering::Relaxed)),
+            slow_polls: self.slow_polls.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A future that records how long each poll of the inner future takes.
+///
+/// The statistics are registered with the executor of the task that first polls the future.
+pub(crate) struct Instrumented<F> {
+    future: F,
+    stats: Arc<TaskStats>,
+    registered: bool,
+}
+
+impl<F: Future> Instrumented<F> {
+    pub(crate) fn new(future: F) -> Self {
+        Self {
+            future,
+            stats: Arc::new(TaskStats::new()),
+            registered: false,
+        }
+    }
+
+    /// Returns a weak reference to the statistics of the future, which are dropped along with it.
+    pub(crate) fn stats(&self) -> Weak<TaskStats> {
+        Arc::downgrade(&self.stats)
+    }
+}
+
+impl<F: Future> Future for Instrumented<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: `future` is never moved out of `self`, and the other fields are never pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+
+        if !this.registered {
+            EXECUTOR.with(|executor| executor.register(&this.stats));
+            this.registered = true;
+        }
+
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+
+        let start = unsafe { pros_sys::micros() };
+        let poll = future.poll(cx);
+        this.stats
+            .record(unsafe { pros_sys::micros() }.saturating_sub(start));
+
+        poll
+    }
+}
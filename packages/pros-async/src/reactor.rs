@@ -1,22 +1,81 @@
-use alloc::collections::BTreeMap;
+use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
 use core::task::Waker;
 
 pub struct Sleepers {
-    sleepers: BTreeMap<u32, Waker>,
+    sleepers: BTreeMap<u32, Vec<Waker>>,
 }
 
 impl Sleepers {
     pub fn push(&mut self, waker: Waker, target: u32) {
-        self.sleepers.insert(target, waker);
+        self.sleepers.entry(target).or_default().push(waker);
     }
 
-    pub fn pop(&mut self) -> Option<Waker> {
-        self.sleepers.pop_first().map(|(_, waker)| waker)
+    /// Removes the wakers of the sleepers with the earliest target,
+    /// as long as that target is before `now`.
+    pub fn pop_due(&mut self, now: u32) -> Option<Vec<Waker>> {
+        let entry = self.sleepers.first_entry()?;
+        (*entry.key() < now).then(|| entry.remove())
+    }
+
+    pub fn pop(&mut self) -> Option<Vec<Waker>> {
+        self.sleepers.pop_first().map(|(_, wakers)| wakers)
+    }
+
+    /// The number of sleepers that are waiting to be woken.
+    pub fn len(&self) -> usize {
+        self.sleepers.values().map(Vec::len).sum()
+    }
+
+    /// The target of the sleeper that will be due next.
+    pub fn next_target(&self) -> Option<u32> {
+        self.sleepers.first_key_value().map(|(target, _)| *target)
+    }
+}
+
+/// A future waiting for a device to be ready, such as for a serial port to receive data.
+struct IoWaiter {
+    waker: Waker,
+    ready: Box<dyn FnMut() -> bool>,
+}
+
+/// Futures waiting for devices that have no way to notify the executor, so they are polled every tick.
+pub struct IoWaiters {
+    waiters: Vec<IoWaiter>,
+}
+
+impl IoWaiters {
+    pub fn push(&mut self, waker: Waker, ready: impl FnMut() -> bool + 'static) {
+        self.waiters.push(IoWaiter {
+            waker,
+            ready: Box::new(ready),
+        });
+    }
+
+    /// Wakes every waiter whose device is ready.
+    pub fn poll(&mut self) {
+        self.waiters.retain_mut(|waiter| {
+            let ready = (waiter.ready)();
+            if ready {
+                waiter.waker.wake_by_ref();
+            }
+            !ready
+        });
+    }
+
+    pub fn wake_all(&mut self) {
+        self.waiters
+            .drain(..)
+            .for_each(|waiter| waiter.waker.wake());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.waiters.is_empty()
     }
 }
 
 pub struct Reactor {
     pub(crate) sleepers: Sleepers,
+    pub(crate) io: IoWaiters,
 }
 
 impl Reactor {
@@ -25,12 +84,26 @@ impl Reactor {
             sleepers: Sleepers {
                 sleepers: BTreeMap::new(),
             },
+            io: IoWaiters {
+                waiters: Vec::new(),
+            },
         }
     }
 
     pub fn tick(&mut self) {
-        if let Some(sleeper) = self.sleepers.pop() {
-            sleeper.wake()
+        let now = unsafe { pros_sys::millis() };
+        while let Some(wakers) = self.sleepers.pop_due(now) {
+            wakers.into_iter().for_each(Waker::wake);
+        }
+
+        self.io.poll();
+    }
+
+    pub fn wake_all(&mut self) {
+        while let Some(wakers) = self.sleepers.pop() {
+            wakers.into_iter().for_each(Waker::wake);
         }
+
+        self.io.wake_all();
     }
 }
@@ -0,0 +1,143 @@
+//! Async barrier.
+
+use alloc::vec::Vec;
+use core::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use pros_core::sync::Mutex;
+
+/// Allows a fixed number of futures to wait for each other to reach the same point.
+///
+/// Once the last future calls [`Barrier::wait`], every waiting future is woken and the barrier
+/// resets so that it can be used again. A [`BarrierWait`] that is dropped before the barrier releases
+/// no longer counts as having arrived.
+pub struct Barrier {
+    count: usize,
+    state: Mutex<BarrierState>,
+}
+
+struct BarrierState {
+    arrived: usize,
+    generation: usize,
+    next_id: usize,
+    /// The ID and waker of each future waiting for the current generation.
+    waiters: Vec<(usize, Waker)>,
+}
+
+impl Barrier {
+    /// Creates a new barrier that releases waiters once `count` of them have called [`Barrier::wait`].
+    ///
+    /// A barrier with a count of zero behaves the same as one with a count of one.
+    pub fn new(count: usize) -> Self {
+        Self {
+            count,
+            state: Mutex::new(BarrierState {
+                arrived: 0,
+                generation: 0,
+                next_id: 0,
+                waiters: Vec::new(),
+            }),
+        }
+    }
+
+    /// Waits until every future using the barrier has called this function.
+    pub const fn wait(&self) -> BarrierWait<'_> {
+        BarrierWait {
+            barrier: self,
+            waiting: None,
+        }
+    }
+}
+
+impl fmt::Debug for Barrier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Barrier")
+            .field("count", &self.count)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Future that waits on a [`Barrier`], created with [`Barrier::wait`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct BarrierWait<'a> {
+    barrier: &'a Barrier,
+    /// The generation this future arrived in and its waiter ID, once it has arrived.
+    waiting: Option<(usize, usize)>,
+}
+
+impl Future for BarrierWait<'_> {
+    type Output = BarrierWaitResult;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let barrier = self.barrier;
+        let mut state = barrier.state.lock().unwrap();
+
+        if let Some((generation, id)) = self.waiting {
+            if state.generation != generation {
+                drop(state);
+                self.waiting = None;
+                return Poll::Ready(BarrierWaitResult { is_leader: false });
+            }
+
+            if let Some((_, waker)) = state.waiters.iter_mut().find(|(other, _)| *other == id) {
+                if !waker.will_wake(cx.waker()) {
+                    *waker = cx.waker().clone();
+                }
+            }
+            return Poll::Pending;
+        }
+
+        state.arrived += 1;
+        if state.arrived >= barrier.count {
+            state.arrived = 0;
+            state.generation = state.generation.wrapping_add(1);
+            let waiters = core::mem::take(&mut state.waiters);
+            drop(state);
+
+            waiters.into_iter().for_each(|(_, waker)| waker.wake());
+            return Poll::Ready(BarrierWaitResult { is_leader: true });
+        }
+
+        let generation = state.generation;
+        let id = state.next_id;
+        state.next_id = state.next_id.wrapping_add(1);
+        state.waiters.push((id, cx.waker().clone()));
+        drop(state);
+        self.waiting = Some((generation, id));
+        Poll::Pending
+    }
+}
+
+impl Drop for BarrierWait<'_> {
+    fn drop(&mut self) {
+        let Some((generation, id)) = self.waiting.take() else {
+            return;
+        };
+
+        // If the barrier hasn't released this future's generation yet, its arrival is undone
+        // so that the barrier still waits for the full count.
+        let mut state = self.barrier.state.lock().unwrap();
+        if state.generation == generation {
+            state.arrived -= 1;
+            state.waiters.retain(|(other, _)| *other != id);
+        }
+    }
+}
+
+/// The result of waiting on a [`Barrier`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarrierWaitResult {
+    is_leader: bool,
+}
+
+impl BarrierWaitResult {
+    /// Returns `true` for exactly one of the futures released by the barrier: the last one to arrive.
+    pub const fn is_leader(&self) -> bool {
+        self.is_leader
+    }
+}
@@ -0,0 +1,20 @@
+//! Async synchronization primitives.
+//!
+//! The types in this module mirror those in [`pros_core::sync`], but waiting on them yields
+//! to the executor instead of blocking the FreeRTOS task. Holding a blocking lock across an
+//! `.await` stalls every other future on the same executor, so these should be preferred in async code.
+//!
+//! All types in this module can be shared between futures on different FreeRTOS tasks,
+//! such as those spawned with [`spawn_on`](crate::spawn_on).
+
+mod barrier;
+mod mutex;
+mod notify;
+mod rwlock;
+mod semaphore;
+
+pub use barrier::{Barrier, BarrierWait, BarrierWaitResult};
+pub use mutex::{Mutex, MutexGuard};
+pub use notify::{Notified, Notify};
+pub use rwlock::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+pub use semaphore::{Acquire, Semaphore, SemaphorePermit};
@@ -0,0 +1,178 @@
+//! Async task notification.
+
+use alloc::{collections::VecDeque, sync::Arc, vec::Vec};
+use core::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicU8, Ordering},
+    task::{Context, Poll, Waker},
+};
+
+use pros_core::sync::Mutex;
+
+const WAITING: u8 = 0;
+const NOTIFIED_ONE: u8 = 1;
+const NOTIFIED_ALL: u8 = 2;
+
+/// Notifies one or many waiting futures of an event.
+///
+/// This is the async counterpart to FreeRTOS task notifications. Calling [`Notify::notify_one`]
+/// when nothing is waiting stores a single permit, so the next call to [`Notify::notified`]
+/// completes immediately.
+pub struct Notify {
+    state: Mutex<NotifyState>,
+}
+
+struct NotifyState {
+    permit: bool,
+    waiters: VecDeque<(Arc<AtomicU8>, Waker)>,
+}
+
+impl Notify {
+    /// Creates a new `Notify` with no stored permit.
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(NotifyState {
+                permit: false,
+                waiters: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Waits for a notification.
+    ///
+    /// The returned future starts waiting the first time it is polled, so calls to
+    /// [`Notify::notify_waiters`] made before then are not observed.
+    pub const fn notified(&self) -> Notified<'_> {
+        Notified {
+            notify: self,
+            state: None,
+        }
+    }
+
+    /// Wakes the future that has been waiting the longest.
+    ///
+    /// If no futures are waiting, a permit is stored for the next one to consume.
+    pub fn notify_one(&self) {
+        let waker = {
+            let mut state = self.state.lock().unwrap();
+            match state.waiters.pop_front() {
+                Some((waiter, waker)) => {
+                    waiter.store(NOTIFIED_ONE, Ordering::Release);
+                    Some(waker)
+                }
+                None => {
+                    state.permit = true;
+                    None
+                }
+            }
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Wakes every future that is currently waiting.
+    ///
+    /// Unlike [`Notify::notify_one`], no permit is stored if nothing is waiting.
+    pub fn notify_waiters(&self) {
+        let wakers: Vec<_> = {
+            let mut state = self.state.lock().unwrap();
+            state
+                .waiters
+                .drain(..)
+                .map(|(waiter, waker)| {
+                    waiter.store(NOTIFIED_ALL, Ordering::Release);
+                    waker
+                })
+                .collect()
+        };
+
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+impl Default for Notify {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Notify {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Notify").finish_non_exhaustive()
+    }
+}
+
+/// Future that waits for a notification from a [`Notify`], created with [`Notify::notified`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Notified<'a> {
+    notify: &'a Notify,
+    state: Option<Arc<AtomicU8>>,
+}
+
+impl Future for Notified<'_> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let notify = self.notify;
+        let mut state = notify.state.lock().unwrap();
+
+        match &self.state {
+            None => {
+                if state.permit {
+                    state.permit = false;
+                    return Poll::Ready(());
+                }
+
+                let waiter = Arc::new(AtomicU8::new(WAITING));
+                state
+                    .waiters
+                    .push_back((waiter.clone(), cx.waker().clone()));
+                drop(state);
+                self.state = Some(waiter);
+                Poll::Pending
+            }
+            Some(waiter) if waiter.load(Ordering::Acquire) != WAITING => {
+                drop(state);
+                self.state = None;
+                Poll::Ready(())
+            }
+            Some(waiter) => {
+                if let Some((_, waker)) = state
+                    .waiters
+                    .iter_mut()
+                    .find(|(other, _)| Arc::ptr_eq(other, waiter))
+                {
+                    waker.clone_from(cx.waker());
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for Notified<'_> {
+    fn drop(&mut self) {
+        let Some(waiter) = self.state.take() else {
+            return;
+        };
+
+        let mut state = self.notify.state.lock().unwrap();
+        match waiter.load(Ordering::Acquire) {
+            WAITING => state
+                .waiters
+                .retain(|(other, _)| !Arc::ptr_eq(other, &waiter)),
+            NOTIFIED_ONE => {
+                // This future was chosen by `notify_one` but never completed,
+                // so the notification is passed on instead of being lost.
+                drop(state);
+                self.notify.notify_one();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Async counting semaphore.
+
+use alloc::{collections::VecDeque, sync::Arc, vec::Vec};
+use core::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll, Waker},
+};
+
+use pros_core::sync::Mutex;
+
+/// A counting semaphore that can be acquired asynchronously.
+///
+/// Waiting to acquire permits yields to the executor instead of blocking the task.
+/// Permits are handed out in the order they were requested, so a large request
+/// will not be starved by smaller ones.
+pub struct Semaphore {
+    state: Mutex<SemaphoreState>,
+}
+
+struct SemaphoreState {
+    permits: usize,
+    waiters: VecDeque<Waiter>,
+}
+
+struct Waiter {
+    permits: usize,
+    granted: Arc<AtomicBool>,
+    waker: Waker,
+}
+
+impl SemaphoreState {
+    /// Grants permits to waiters at the front of the queue until there are not enough permits left,
+    /// returning the wakers of the waiters that were granted permits.
+    fn grant(&mut self) -> Vec<Waker> {
+        let mut wakers = Vec::new();
+
+        while let Some(waiter) = self.waiters.front() {
+            if waiter.permits > self.permits {
+                break;
+            }
+
+            let waiter = self.waiters.pop_front().unwrap();
+            self.permits -= waiter.permits;
+            waiter.granted.store(true, Ordering::Release);
+            wakers.push(waiter.waker);
+        }
+
+        wakers
+    }
+}
+
+impl Semaphore {
+    /// Creates a new semaphore with the given number of permits.
+    pub fn new(permits: usize) -> Self {
+        Self {
+            state: Mutex::new(SemaphoreState {
+                permits,
+                waiters: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Returns the number of permits that are currently available.
+    pub fn available_permits(&self) -> usize {
+        self.state.lock().unwrap().permits
+    }
+
+    /// Adds permits to the semaphore, waking any waiters that can now acquire theirs.
+    pub fn add_permits(&self, permits: usize) {
+        let wakers = {
+            let mut state = self.state.lock().unwrap();
+            state.permits += permits;
+            state.grant()
+        };
+
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    /// Waits until a permit can be acquired.
+    pub const fn acquire(&self) -> Acquire<'_> {
+        self.acquire_many(1)
+    }
+
+    /// Waits until the given number of permits can be acquired at once.
+    pub const fn acquire_many(&self, permits: usize) -> Acquire<'_> {
+        Acquire {
+            semaphore: self,
+            permits,
+            granted: None,
+        }
+    }
+
+    /// Attempts to acquire a permit without waiting.
+    pub fn try_acquire(&self) -> Option<SemaphorePermit<'_>> {
+        self.try_acquire_many(1)
+    }
+
+    /// Attempts to acquire the given number of permits without waiting.
+    ///
+    /// This fails if other tasks are already waiting for permits, even if enough are available.
+    pub fn try_acquire_many(&self, permits: usize) -> Option<SemaphorePermit<'_>> {
+        let mut state = self.state.lock().unwrap();
+
+        if state.waiters.is_empty() && state.permits >= permits {
+            state.permits -= permits;
+            Some(SemaphorePermit {
+                semaphore: self,
+                permits,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Debug for Semaphore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Semaphore")
+            .field("permits", &self.available_permits())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Future that acquires permits from a [`Semaphore`],
+/// created with [`Semaphore::acquire`] or [`Semaphore::acquire_many`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Acquire<'a> {
+    semaphore: &'a Semaphore,
+    permits: usize,
+    granted: Option<Arc<AtomicBool>>,
+}
+
+impl<'a> Future for Acquire<'a> {
+    type Output = SemaphorePermit<'a>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let semaphore = self.semaphore;
+        let permits = self.permits;
+        let mut state = semaphore.state.lock().unwrap();
+
+        match &self.granted {
+            None => {
+                if state.waiters.is_empty() && state.permits >= permits {
+                    state.permits -= permits;
+                    return Poll::Ready(SemaphorePermit { semaphore, permits });
+                }
+
+                let granted = Arc::new(AtomicBool::new(false));
+                state.waiters.push_back(Waiter {
+                    permits,
+                    granted: granted.clone(),
+                    waker: cx.waker().clone(),
+                });
+                drop(state);
+                self.granted = Some(granted);
+                Poll::Pending
+            }
+            Some(granted) if granted.load(Ordering::Acquire) => {
+                drop(state);
+                self.granted = None;
+                Poll::Ready(SemaphorePermit { semaphore, permits })
+            }
+            Some(granted) => {
+                if let Some(waiter) = state
+                    .waiters
+                    .iter_mut()
+                    .find(|waiter| Arc::ptr_eq(&waiter.granted, granted))
+                {
+                    waiter.waker.clone_from(cx.waker());
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        let Some(granted) = self.granted.take() else {
+            return;
+        };
+
+        let wakers = {
+            let mut state = self.semaphore.state.lock().unwrap();
+
+            if granted.load(Ordering::Acquire) {
+                // The permits were granted, but never handed out.
+                state.permits += self.permits;
+            } else {
+                state
+                    .waiters
+                    .retain(|waiter| !Arc::ptr_eq(&waiter.granted, &granted));
+            }
+
+            // Removing this waiter may have unblocked the ones behind it.
+            state.grant()
+        };
+
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+/// Permits acquired from a [`Semaphore`].
+///
+/// The permits are returned to the semaphore when this is dropped.
+#[derive(Debug)]
+#[must_use = "permits are released immediately if unused"]
+pub struct SemaphorePermit<'a> {
+    semaphore: &'a Semaphore,
+    permits: usize,
+}
+
+impl SemaphorePermit<'_> {
+    /// Returns the number of permits held.
+    pub const fn permits(&self) -> usize {
+        self.permits
+    }
+
+    /// Forgets the permits without returning them to the semaphore.
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        if self.permits > 0 {
+            self.semaphore.add_permits(self.permits);
+        }
+    }
+}
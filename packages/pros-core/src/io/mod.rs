@@ -3,6 +3,8 @@
 //! Implements `println!`, `eprintln!` and `dbg!` on top of the `pros_sys` crate without requiring
 //! the use of an allocator. (Modified version of `libc_print` crate)
 //!
+//! Text typed into the serial terminal can be read with [`stdin`].
+//!
 //! Allows you to use these macros in a #!\[no_std\] context, or in a situation where the
 //! traditional Rust streams might not be available (ie: at process shutdown time).
 //!
@@ -63,9 +65,12 @@
 use core::{convert::TryFrom, file, line, stringify};
 
 pub use no_std_io::io::*;
+pub use stdin::{stdin, Stdin};
 
 pub use crate::{dbg, eprint, eprintln, print, println};
 
+mod stdin;
+
 #[doc(hidden)]
 #[allow(missing_debug_implementations)]
 pub struct __SerialWriter(i32);
@@ -0,0 +1,107 @@
+//! Reading from the standard input.
+
+use alloc::{string::String, vec::Vec};
+use core::time::Duration;
+
+use no_std_io::io;
+
+use crate::task::delay;
+
+/// The file descriptor of the standard input.
+const STDIN_FILENO: i32 = 0;
+
+/// How often blocking reads check for new input.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// A handle to the standard input, which receives text typed into the serial terminal.
+///
+/// Created with [`stdin`]. All handles read from the same input, so when several tasks read at once,
+/// each byte is only received by one of them.
+#[derive(Debug, Clone, Copy)]
+pub struct Stdin {
+    _private: (),
+}
+
+/// Returns a handle to the standard input.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut line = String::new();
+/// stdin().read_line(&mut line)?;
+/// match line.trim() {
+///     "auton" => run_autonomous(),
+///     _ => println!("unknown command"),
+/// }
+/// ```
+pub const fn stdin() -> Stdin {
+    Stdin { _private: () }
+}
+
+impl Stdin {
+    /// Returns the number of bytes that can be read without blocking.
+    pub fn bytes_available(&self) -> usize {
+        let available = unsafe {
+            pros_sys::apix::fdctl(
+                STDIN_FILENO,
+                pros_sys::apix::DEVCTL_FIONREAD,
+                core::ptr::null_mut(),
+            )
+        };
+        usize::try_from(available).unwrap_or(0)
+    }
+
+    /// Reads the bytes that are already available into `buf`, without blocking.
+    ///
+    /// Returns the number of bytes read, which is zero if no input is available.
+    pub fn try_read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.bytes_available());
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let read = unsafe { pros_sys::read(STDIN_FILENO, buf.as_mut_ptr().cast(), len) };
+        usize::try_from(read)
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "failed to read from stdin"))
+    }
+
+    /// Reads a line of input and appends it to `buf`, blocking until a newline is received.
+    ///
+    /// The newline is included in `buf`, so it usually needs to be trimmed. Returns the number of bytes read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`](io::ErrorKind::InvalidData) if the line is not valid UTF-8,
+    /// in which case nothing is appended to `buf`.
+    pub fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        let mut line = Vec::new();
+        let mut byte = [0];
+        while !line.ends_with(b"\n") {
+            // Bytes are read one at a time so that input after the newline is left for the next read.
+            io::Read::read(self, &mut byte)?;
+            line.push(byte[0]);
+        }
+
+        let line = String::from_utf8(line)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "stdin is not valid UTF-8"))?;
+        buf.push_str(&line);
+        Ok(line.len())
+    }
+}
+
+impl io::Read for Stdin {
+    /// Reads available bytes into `buf`, blocking until at least one byte is available.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            let read = self.try_read(buf)?;
+            if read > 0 {
+                return Ok(read);
+            }
+            delay(POLL_INTERVAL);
+        }
+    }
+}
@@ -3,11 +3,16 @@
 //!
 //! Included in this crate:
 //! - Global allocator: [`pros_alloc`]
+//! - Competition status: [`competition`]
 //! - Errno handling: [`error`]
 //! - Serial terminal printing: [`io`]
+//! - Leveled logging: [`logger`]
 //! - No-std [`Instant`](time::Instant)s: [`time`]
 //! - Synchronization primitives: [`sync`]
 //! - FreeRTOS task management: [`task`]
+//! - Binary telemetry streaming: [`telemetry`]
+//! - Stalled task detection: [`watchdog`]
+//! - Software timers: [`timer`]
 
 #![no_std]
 #![feature(error_in_core)]
@@ -15,8 +20,13 @@
 extern crate alloc;
 
 pub mod allocator;
+pub mod competition;
 pub mod error;
 pub mod io;
+pub mod logger;
 pub mod sync;
 pub mod task;
+pub mod telemetry;
 pub mod time;
+pub mod timer;
+pub mod watchdog;
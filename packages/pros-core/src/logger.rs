@@ -0,0 +1,353 @@
+//! Leveled logging with pluggable sinks.
+//!
+//! This module provides a backend for the [`log`] crate, so the usual [`info!`], [`warn!`] and friends
+//! can be used anywhere in a program. Each log entry is tagged with the time since the program started,
+//! the name of the task that logged it and the module it came from, and is then written to every
+//! [`Sink`] whose level allows it.
+//!
+//! Levels can be set per module, and a separate, usually quieter, level can be applied automatically
+//! while the robot is connected to field control or a competition switch. This allows verbose debug
+//! output during practice without it slowing the robot down during matches.
+//!
+//! # Example
+//!
+//! ```ignore
+//! Logger::builder()
+//!     .level(LevelFilter::Debug)
+//!     .module_level("my_robot::odometry", LevelFilter::Trace)
+//!     .competition_level(LevelFilter::Warn)
+//!     .sink(SerialSink)
+//!     .sink_with_level(FileSink::new("/usd/robot.log"), LevelFilter::Info)
+//!     .init()
+//!     .unwrap();
+//!
+//! info!("robot initialized");
+//! ```
+
+use alloc::{
+    boxed::Box,
+    ffi::CString,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt, fmt::Write as _, time::Duration};
+
+pub use log::{debug, error, info, trace, warn, Level, LevelFilter, SetLoggerError};
+use log::{Log, Metadata, Record};
+
+use crate::{io::__println, sync::Mutex, task, time::Instant};
+
+/// A single log entry, passed to every [`Sink`] that accepts its level.
+///
+/// The [`Display`](fmt::Display) implementation formats the entry as a single line, like
+/// `[   12.345s INFO  opcontrol my_robot::drive] message`.
+#[derive(Debug, Clone, Copy)]
+pub struct LogEntry<'a> {
+    /// The level of the entry.
+    pub level: Level,
+    /// The target of the entry, which is the module path it was logged from unless overridden.
+    pub target: &'a str,
+    /// The name of the task that logged the entry.
+    pub task: &'a str,
+    /// The time between the start of the program and when the entry was logged.
+    pub uptime: Duration,
+    /// The message.
+    pub args: fmt::Arguments<'a>,
+}
+
+impl fmt::Display for LogEntry<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{:>6}.{:03}s {:<5} {} {}] {}",
+            self.uptime.as_secs(),
+            self.uptime.subsec_millis(),
+            self.level,
+            self.task,
+            self.target,
+            self.args
+        )
+    }
+}
+
+/// A destination for log entries.
+///
+/// Sinks can be written to from any task, possibly at the same time, so they must handle their own locking.
+/// Sinks must not log anything themselves.
+pub trait Sink: Send + Sync {
+    /// Writes a log entry.
+    fn write(&self, entry: &LogEntry<'_>);
+
+    /// Writes out any buffered entries.
+    fn flush(&self) {}
+}
+
+/// A sink that prints log entries to the serial terminal.
+///
+/// Errors and warnings are printed to stderr, and everything else to stdout.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SerialSink;
+
+impl Sink for SerialSink {
+    fn write(&self, entry: &LogEntry<'_>) {
+        if entry.level <= Level::Warn {
+            crate::eprintln!("{entry}");
+        } else {
+            crate::println!("{entry}");
+        }
+    }
+}
+
+/// A sink that appends log entries to a file, such as a file on the microSD card.
+///
+/// Writing to the microSD card is slow, so entries are buffered and written in batches.
+/// The buffer is written when it is full, when an error or warning is logged, and when the logger is flushed.
+#[derive(Debug)]
+pub struct FileSink {
+    path: CString,
+    buffer: Mutex<String>,
+    capacity: usize,
+}
+
+impl FileSink {
+    /// The default size of the buffer, in bytes.
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    /// Creates a sink that appends to the file at `path`, creating it if it doesn't exist.
+    ///
+    /// Files on the microSD card must be prefixed with `/usd/`, like `/usd/robot.log`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` contains a null byte.
+    pub fn new(path: &str) -> Self {
+        Self::with_capacity(path, Self::DEFAULT_CAPACITY)
+    }
+
+    /// Creates a sink that appends to the file at `path`, buffering up to `capacity` bytes between writes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` contains a null byte.
+    pub fn with_capacity(path: &str, capacity: usize) -> Self {
+        Self {
+            path: CString::new(path).expect("path should not contain null bytes"),
+            buffer: Mutex::new(String::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    fn write_buffer(&self, buffer: &mut String) {
+        if buffer.is_empty() {
+            return;
+        }
+
+        unsafe {
+            let fd = pros_sys::open(
+                self.path.as_ptr(),
+                pros_sys::O_WRONLY | pros_sys::O_CREAT | pros_sys::O_APPEND,
+            );
+            // If the file can't be opened, the entries are dropped rather than buffered forever.
+            if fd >= 0 {
+                _ = __println(fd, buffer);
+                pros_sys::close(fd);
+            }
+        }
+
+        buffer.clear();
+    }
+}
+
+impl Sink for FileSink {
+    fn write(&self, entry: &LogEntry<'_>) {
+        let mut buffer = self.buffer.lock_unpoisoned();
+        _ = writeln!(buffer, "{entry}");
+
+        if buffer.len() >= self.capacity || entry.level <= Level::Warn {
+            self.write_buffer(&mut buffer);
+        }
+    }
+
+    fn flush(&self) {
+        self.write_buffer(&mut self.buffer.lock_unpoisoned());
+    }
+}
+
+struct SinkEntry {
+    sink: Box<dyn Sink>,
+    level: LevelFilter,
+}
+
+/// A logger that writes to a set of [`Sink`]s. Created with [`Logger::builder`].
+pub struct Logger {
+    level: LevelFilter,
+    competition_level: Option<LevelFilter>,
+    modules: Vec<(String, LevelFilter)>,
+    sinks: Vec<SinkEntry>,
+}
+
+impl Logger {
+    /// Creates a builder for a logger.
+    ///
+    /// By default, the logger logs entries at [`Level::Info`] and above, and has no sinks.
+    pub const fn builder() -> LoggerBuilder {
+        LoggerBuilder {
+            logger: Self {
+                level: LevelFilter::Info,
+                competition_level: None,
+                modules: Vec::new(),
+                sinks: Vec::new(),
+            },
+        }
+    }
+
+    /// Returns the level filter that applies to `target`, taking competition control into account.
+    fn level_for(&self, target: &str) -> LevelFilter {
+        // Modules are sorted from most to least specific, so the first match is the most specific one.
+        let level = self
+            .modules
+            .iter()
+            .find(|(module, _)| {
+                target
+                    .strip_prefix(module.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .map_or(self.level, |(_, level)| *level);
+
+        match self.competition_level {
+            Some(competition_level) if competition_connected() => level.min(competition_level),
+            _ => level,
+        }
+    }
+
+    /// The most verbose level that any entry could be logged at.
+    fn max_level(&self) -> LevelFilter {
+        let filters = self
+            .modules
+            .iter()
+            .map(|(_, level)| *level)
+            .chain([self.level])
+            .max()
+            .unwrap_or(self.level);
+        let sinks = self
+            .sinks
+            .iter()
+            .map(|sink| sink.level)
+            .max()
+            .unwrap_or(LevelFilter::Off);
+
+        filters.min(sinks)
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= self.level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let task = task::current()
+            .name()
+            .unwrap_or_else(|_| "<unknown>".to_string());
+        let entry = LogEntry {
+            level: record.level(),
+            target: record.target(),
+            task: &task,
+            uptime: Instant::now().since_start(),
+            args: *record.args(),
+        };
+
+        for sink in &self.sinks {
+            if entry.level <= sink.level {
+                sink.sink.write(&entry);
+            }
+        }
+    }
+
+    fn flush(&self) {
+        for sink in &self.sinks {
+            sink.sink.flush();
+        }
+    }
+}
+
+impl fmt::Debug for Logger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Logger")
+            .field("level", &self.level)
+            .field("competition_level", &self.competition_level)
+            .field("modules", &self.modules)
+            .field("sinks", &self.sinks.len())
+            .finish()
+    }
+}
+
+/// A builder for a [`Logger`], created with [`Logger::builder`].
+#[derive(Debug)]
+#[must_use = "the logger is not used until `init` is called"]
+pub struct LoggerBuilder {
+    logger: Logger,
+}
+
+impl LoggerBuilder {
+    /// Sets the level of entries to log from modules without their own level.
+    pub const fn level(mut self, level: LevelFilter) -> Self {
+        self.logger.level = level;
+        self
+    }
+
+    /// Sets the level of entries to log from `module` and its submodules, overriding the default level.
+    ///
+    /// `module` is a module path such as `my_robot::odometry`, or a crate name such as `pros_devices`.
+    pub fn module_level(mut self, module: &str, level: LevelFilter) -> Self {
+        self.logger.modules.retain(|(other, _)| other != module);
+        self.logger.modules.push((module.to_string(), level));
+        self.logger
+            .modules
+            .sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()));
+        self
+    }
+
+    /// Sets the most verbose level to log while the robot is connected to field control or a competition switch.
+    ///
+    /// This takes priority over every other level, so that verbose debug output can be left enabled for
+    /// practice without affecting matches.
+    pub const fn competition_level(mut self, level: LevelFilter) -> Self {
+        self.logger.competition_level = Some(level);
+        self
+    }
+
+    /// Adds a sink that receives every logged entry.
+    pub fn sink(self, sink: impl Sink + 'static) -> Self {
+        self.sink_with_level(sink, LevelFilter::Trace)
+    }
+
+    /// Adds a sink that only receives entries at `level` and above.
+    pub fn sink_with_level(mut self, sink: impl Sink + 'static, level: LevelFilter) -> Self {
+        self.logger.sinks.push(SinkEntry {
+            sink: Box::new(sink),
+            level,
+        });
+        self
+    }
+
+    /// Installs the logger as the global logger for the [`log`] crate.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a global logger has already been installed.
+    pub fn init(self) -> Result<(), SetLoggerError> {
+        let max_level = self.logger.max_level();
+        log::set_logger(Box::leak(Box::new(self.logger)))?;
+        log::set_max_level(max_level);
+        Ok(())
+    }
+}
+
+fn competition_connected() -> bool {
+    unsafe { pros_sys::misc::competition_get_status() & pros_sys::misc::COMPETITION_CONNECTED != 0 }
+}
@@ -0,0 +1,163 @@
+use alloc::{collections::VecDeque, sync::Arc};
+use core::{fmt::Debug, time::Duration};
+
+use super::{LockResult, Mutex, MutexGuard, PoisonError, Semaphore};
+
+/// A condition variable.
+///
+/// Condition variables block a task until some condition becomes true, and are always paired with a [`Mutex`]
+/// protecting the data the condition depends on. Each waiting task blocks on its own binary [`Semaphore`],
+/// so waiting does not consume task notifications that may be used for other purposes.
+///
+/// Waiting tasks may wake up spuriously, so the condition should always be checked in a loop,
+/// or with [`Condvar::wait_while`].
+pub struct Condvar {
+    waiters: Mutex<VecDeque<Arc<Semaphore>>>,
+}
+
+/// Whether a timed wait on a [`Condvar`] returned because the timeout elapsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaitTimeoutResult(bool);
+
+impl WaitTimeoutResult {
+    /// Returns `true` if the wait timed out.
+    pub const fn timed_out(&self) -> bool {
+        self.0
+    }
+}
+
+impl Condvar {
+    /// Creates a new condition variable.
+    pub fn new() -> Self {
+        Self {
+            waiters: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Unlocks the mutex guarded by `guard` and blocks the current task until this condition variable is notified.
+    /// The mutex is locked again before returning.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PoisonError`] containing the guard if the mutex was poisoned when it was locked again.
+    pub fn wait<'a, T>(&self, guard: MutexGuard<'a, T>) -> LockResult<MutexGuard<'a, T>> {
+        let mutex = guard.mutex;
+        let signal = self.enqueue();
+
+        drop(guard);
+        signal.wait();
+
+        mutex.lock()
+    }
+
+    /// Blocks the current task while `condition` returns `true`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PoisonError`] containing the guard if the mutex was poisoned when it was locked again.
+    pub fn wait_while<'a, T, F>(
+        &self,
+        mut guard: MutexGuard<'a, T>,
+        mut condition: F,
+    ) -> LockResult<MutexGuard<'a, T>>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        while condition(&mut *guard) {
+            guard = self.wait(guard)?;
+        }
+        Ok(guard)
+    }
+
+    /// Unlocks the mutex guarded by `guard` and blocks the current task until this condition variable is notified
+    /// or `timeout` elapses. The mutex is locked again before returning.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PoisonError`] containing the guard if the mutex was poisoned when it was locked again.
+    pub fn wait_timeout<'a, T>(
+        &self,
+        guard: MutexGuard<'a, T>,
+        timeout: Duration,
+    ) -> LockResult<(MutexGuard<'a, T>, WaitTimeoutResult)> {
+        let mutex = guard.mutex;
+        let signal = self.enqueue();
+
+        drop(guard);
+        let mut timed_out = !signal.wait_timeout(timeout);
+
+        if timed_out {
+            let mut waiters = self.waiters.lock_unpoisoned();
+            match waiters
+                .iter()
+                .position(|waiter| Arc::ptr_eq(waiter, &signal))
+            {
+                Some(index) => _ = waiters.remove(index),
+                // We were notified after the timeout elapsed, but before we could stop waiting.
+                None => timed_out = false,
+            }
+        }
+
+        let result = WaitTimeoutResult(timed_out);
+        match mutex.lock() {
+            Ok(guard) => Ok((guard, result)),
+            Err(err) => Err(PoisonError::new((err.into_inner(), result))),
+        }
+    }
+
+    /// Blocks the current task while `condition` returns `true`, for no longer than `timeout`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PoisonError`] containing the guard if the mutex was poisoned when it was locked again.
+    pub fn wait_timeout_while<'a, T, F>(
+        &self,
+        mut guard: MutexGuard<'a, T>,
+        timeout: Duration,
+        mut condition: F,
+    ) -> LockResult<(MutexGuard<'a, T>, WaitTimeoutResult)>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let start = crate::time::Instant::now();
+        while condition(&mut *guard) {
+            let Some(remaining) = timeout.checked_sub(start.elapsed()) else {
+                return Ok((guard, WaitTimeoutResult(true)));
+            };
+            guard = self.wait_timeout(guard, remaining)?.0;
+        }
+        Ok((guard, WaitTimeoutResult(false)))
+    }
+
+    /// Wakes up the task that has been waiting on this condition variable the longest.
+    pub fn notify_one(&self) {
+        if let Some(waiter) = self.waiters.lock_unpoisoned().pop_front() {
+            waiter.post();
+        }
+    }
+
+    /// Wakes up every task that is waiting on this condition variable.
+    pub fn notify_all(&self) {
+        for waiter in self.waiters.lock_unpoisoned().drain(..) {
+            waiter.post();
+        }
+    }
+
+    fn enqueue(&self) -> Arc<Semaphore> {
+        let signal = Arc::new(Semaphore::binary());
+        self.waiters.lock_unpoisoned().push_back(signal.clone());
+        signal
+    }
+}
+
+impl Default for Condvar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for Condvar {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Condvar").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,240 @@
+//! Synchronization types for FreeRTOS tasks.
+//!
+//! Types implemented here are specifically designed to mimic the standard library.
+
+use core::{
+    cell::UnsafeCell,
+    fmt::Debug,
+    marker::PhantomData,
+    mem,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+pub use condvar::{Condvar, WaitTimeoutResult};
+pub use once::{LazyLock, Once, OnceLock};
+pub(crate) use poison::{poison_held_locks, track_held_locks};
+pub use poison::{LockResult, PoisonError, TryLockError, TryLockResult};
+pub use queue::Queue;
+pub use recursive_mutex::{RecursiveMutex, RecursiveMutexGuard};
+pub use rwlock::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+pub use semaphore::Semaphore;
+
+use crate::error::take_errno;
+
+mod condvar;
+mod once;
+mod poison;
+mod queue;
+mod recursive_mutex;
+mod rwlock;
+mod semaphore;
+
+/// Converts a timeout into the number of milliseconds FreeRTOS should block for,
+/// saturating to [`pros_sys::TIMEOUT_MAX`].
+pub(crate) fn timeout_millis(timeout: Duration) -> u32 {
+    u32::try_from(timeout.as_millis()).unwrap_or(pros_sys::TIMEOUT_MAX)
+}
+
+/// The basic mutex type.
+/// Mutexes are used to share variables between tasks safely.
+///
+/// # Poisoning
+///
+/// If a task panics while holding the lock, the mutex is poisoned and unlocked before the task is deleted.
+/// Locking a poisoned mutex returns a [`PoisonError`] containing the guard, so that the data can still be
+/// accessed after checking that it is in a valid state. Tasks that panic are only deleted if they were
+/// spawned with a [`JoinHandle`](crate::task::JoinHandle); otherwise, the program exits.
+pub struct Mutex<T> {
+    pros_mutex: pros_sys::mutex_t,
+    poisoned: AtomicBool,
+    data: Option<UnsafeCell<T>>,
+}
+unsafe impl<T: Send> Send for Mutex<T> {}
+unsafe impl<T> Sync for Mutex<T> {}
+
+impl<T> Mutex<T> {
+    /// Creates a new mutex.
+    pub fn new(data: T) -> Self {
+        let pros_mutex = unsafe { pros_sys::mutex_create() };
+
+        Self {
+            pros_mutex,
+            poisoned: AtomicBool::new(false),
+            data: Some(UnsafeCell::new(data)),
+        }
+    }
+
+    /// Locks the mutex so that it cannot be locked in another task at the same time.
+    /// Blocks the current task until the lock is acquired.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PoisonError`] if another task panicked while holding the lock.
+    /// The lock is still acquired, and the guard can be recovered from the error.
+    pub fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
+        if !unsafe { pros_sys::mutex_take(self.pros_mutex, pros_sys::TIMEOUT_MAX) } {
+            panic!("Mutex lock failed: {}", take_errno());
+        }
+
+        self.guard()
+    }
+
+    /// Attempts to acquire this lock. This function does not block.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryLockError::WouldBlock`] if the lock is held by another task,
+    /// or [`TryLockError::Poisoned`] if another task panicked while holding the lock.
+    pub fn try_lock(&self) -> TryLockResult<MutexGuard<'_, T>> {
+        self.lock_timeout(Duration::ZERO)
+    }
+
+    /// Attempts to acquire this lock, blocking the current task for no longer than `timeout`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryLockError::WouldBlock`] if the lock could not be acquired before `timeout` elapsed,
+    /// or [`TryLockError::Poisoned`] if another task panicked while holding the lock.
+    pub fn lock_timeout(&self, timeout: Duration) -> TryLockResult<MutexGuard<'_, T>> {
+        if unsafe { pros_sys::mutex_take(self.pros_mutex, timeout_millis(timeout)) } {
+            Ok(self.guard()?)
+        } else {
+            Err(TryLockError::WouldBlock)
+        }
+    }
+
+    /// Locks the mutex, ignoring poisoning.
+    ///
+    /// This is used for internal state that is never left invalid by a panic.
+    pub(crate) fn lock_unpoisoned(&self) -> MutexGuard<'_, T> {
+        self.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns `true` if a task panicked while holding the lock.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::Relaxed)
+    }
+
+    /// Clears the poisoned state of the mutex.
+    ///
+    /// This should only be done after making sure that the protected data is valid again.
+    pub fn clear_poison(&self) {
+        self.poisoned.store(false, Ordering::Relaxed);
+    }
+
+    /// Consumes the mutex and returns the inner data.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PoisonError`] containing the data if another task panicked while holding the lock.
+    pub fn into_inner(mut self) -> LockResult<T> {
+        let data = mem::take(&mut self.data).unwrap().into_inner();
+        poison::map_result(data, self.is_poisoned())
+    }
+
+    /// Gets a mutable reference to the inner data.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PoisonError`] containing the reference if another task panicked while holding the lock.
+    pub fn get_mut(&mut self) -> LockResult<&mut T> {
+        let poisoned = self.is_poisoned();
+        poison::map_result(self.data.as_mut().unwrap().get_mut(), poisoned)
+    }
+
+    /// Creates a guard for the lock, which must already be held by the current task.
+    fn guard(&self) -> LockResult<MutexGuard<'_, T>> {
+        poison::acquired(self.pros_mutex, &self.poisoned);
+        poison::map_result(MutexGuard::new(self), self.is_poisoned())
+    }
+}
+
+impl<T> Drop for Mutex<T> {
+    fn drop(&mut self) {
+        unsafe {
+            pros_sys::mutex_delete(self.pros_mutex);
+        }
+    }
+}
+
+impl<T> Debug for Mutex<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        struct Placeholder;
+        impl Debug for Placeholder {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.write_str("<locked>")
+            }
+        }
+
+        let mut d = f.debug_struct("Mutex");
+        match self.try_lock() {
+            Ok(guard) => d.field("data", &&*guard),
+            Err(TryLockError::Poisoned(err)) => d.field("data", &&**err.get_ref()),
+            Err(TryLockError::WouldBlock) => d.field("data", &Placeholder),
+        };
+        d.field("poisoned", &self.is_poisoned());
+        d.finish_non_exhaustive()
+    }
+}
+
+impl<T> Default for Mutex<T>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for Mutex<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Allows the user to access the data from a locked mutex.
+/// Dereference to get the inner data.
+///
+/// The guard must be dropped on the task that locked the mutex, so it is not [`Send`].
+#[derive(Debug)]
+pub struct MutexGuard<'a, T> {
+    mutex: &'a Mutex<T>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<'a, T> MutexGuard<'a, T> {
+    const fn new(mutex: &'a Mutex<T>) -> Self {
+        Self {
+            mutex,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+unsafe impl<T: Sync> Sync for MutexGuard<'_, T> {}
+
+impl<T> core::ops::Deref for MutexGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.mutex.data.as_ref().unwrap().get() }
+    }
+}
+
+impl<T> core::ops::DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.mutex.data.as_ref().unwrap().get() }
+    }
+}
+
+impl<T> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        poison::released(self.mutex.pros_mutex);
+        unsafe {
+            pros_sys::mutex_give(self.mutex.pros_mutex);
+        }
+    }
+}
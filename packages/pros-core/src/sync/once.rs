@@ -0,0 +1,247 @@
+use core::{
+    cell::UnsafeCell,
+    fmt::Debug,
+    mem::MaybeUninit,
+    ops::Deref,
+    sync::atomic::{AtomicU8, Ordering},
+    time::Duration,
+};
+
+const INCOMPLETE: u8 = 0;
+const RUNNING: u8 = 1;
+const COMPLETE: u8 = 2;
+
+/// How long a task waits before checking if another task has finished running a [`Once`].
+///
+/// Waiting tasks are delayed instead of spinning so that a lower priority task
+/// that is running the initializer is not starved.
+const WAIT_INTERVAL: Duration = Duration::from_millis(1);
+
+/// A synchronization primitive which can be used to run a one-time global initialization.
+///
+/// Unlike most types in this module, `Once` can be created in a `const` context, so it can be used in statics.
+pub struct Once {
+    state: AtomicU8,
+}
+
+impl Once {
+    /// Creates a new `Once` value.
+    pub const fn new() -> Self {
+        Self {
+            state: AtomicU8::new(INCOMPLETE),
+        }
+    }
+
+    /// Runs `f` if this is the first time `call_once` has been called on this `Once`.
+    ///
+    /// If another task is currently running its initializer, this blocks the current task until it completes.
+    /// It is guaranteed that once this returns, some initializer has finished running.
+    pub fn call_once<F: FnOnce()>(&self, f: F) {
+        if self.is_completed() {
+            return;
+        }
+
+        loop {
+            match self.state.compare_exchange(
+                INCOMPLETE,
+                RUNNING,
+                Ordering::Acquire,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    f();
+                    self.state.store(COMPLETE, Ordering::Release);
+                    return;
+                }
+                Err(COMPLETE) => return,
+                Err(_) => crate::task::delay(WAIT_INTERVAL),
+            }
+        }
+    }
+
+    /// Returns `true` if some initializer has finished running.
+    pub fn is_completed(&self) -> bool {
+        self.state.load(Ordering::Acquire) == COMPLETE
+    }
+}
+
+impl Default for Once {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for Once {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Once").finish_non_exhaustive()
+    }
+}
+
+/// A cell which can be written to only once.
+///
+/// This is useful for shared state that is initialized at runtime, such as a value that is
+/// computed in `initialize` and then read by every other task.
+pub struct OnceLock<T> {
+    once: Once,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+unsafe impl<T: Send> Send for OnceLock<T> {}
+unsafe impl<T: Send + Sync> Sync for OnceLock<T> {}
+
+impl<T> OnceLock<T> {
+    /// Creates a new empty cell.
+    pub const fn new() -> Self {
+        Self {
+            once: Once::new(),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /// Gets a reference to the value, or `None` if the cell has not been initialized yet.
+    pub fn get(&self) -> Option<&T> {
+        // SAFETY: The value is initialized once `once` is complete, and is never written to again.
+        self.once
+            .is_completed()
+            .then(|| unsafe { (*self.value.get()).assume_init_ref() })
+    }
+
+    /// Gets a mutable reference to the value, or `None` if the cell has not been initialized yet.
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        self.once
+            .is_completed()
+            .then(|| unsafe { self.value.get_mut().assume_init_mut() })
+    }
+
+    /// Initializes the cell with `value`.
+    ///
+    /// If the cell was already initialized, `value` is returned in the error.
+    pub fn set(&self, value: T) -> Result<(), T> {
+        let mut value = Some(value);
+        self.get_or_init(|| value.take().unwrap());
+
+        match value {
+            None => Ok(()),
+            Some(value) => Err(value),
+        }
+    }
+
+    /// Gets a reference to the value, initializing it with `f` if the cell is empty.
+    ///
+    /// If another task is initializing the cell, this blocks the current task until it is done.
+    pub fn get_or_init<F: FnOnce() -> T>(&self, f: F) -> &T {
+        self.once.call_once(|| {
+            // SAFETY: Only one task can run the initializer, and no references to the value exist yet.
+            unsafe { (*self.value.get()).write(f()) };
+        });
+
+        // SAFETY: `call_once` only returns once the value has been initialized.
+        unsafe { (*self.value.get()).assume_init_ref() }
+    }
+
+    /// Consumes the cell and returns the value, or `None` if it was never initialized.
+    pub fn into_inner(mut self) -> Option<T> {
+        self.take()
+    }
+
+    /// Takes the value out of the cell, leaving it uninitialized.
+    pub fn take(&mut self) -> Option<T> {
+        if !self.once.is_completed() {
+            return None;
+        }
+
+        self.once = Once::new();
+        // SAFETY: The cell was initialized, and `once` has been reset so the value won't be read again.
+        Some(unsafe { self.value.get_mut().assume_init_read() })
+    }
+}
+
+impl<T> Drop for OnceLock<T> {
+    fn drop(&mut self) {
+        if self.once.is_completed() {
+            unsafe { self.value.get_mut().assume_init_drop() };
+        }
+    }
+}
+
+impl<T> Default for OnceLock<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<T> for OnceLock<T> {
+    fn from(value: T) -> Self {
+        let cell = Self::new();
+        _ = cell.set(value);
+        cell
+    }
+}
+
+impl<T: Debug> Debug for OnceLock<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut d = f.debug_tuple("OnceLock");
+        match self.get() {
+            Some(value) => d.field(value),
+            None => d.field(&format_args!("<uninit>")),
+        };
+        d.finish()
+    }
+}
+
+/// A value which is initialized the first time it is accessed.
+///
+/// This can be used in statics to share state between tasks that can't be created in a `const` context.
+///
+/// # Examples
+///
+/// ```ignore
+/// static POSE: LazyLock<Mutex<Pose>> = LazyLock::new(|| Mutex::new(Pose::default()));
+/// ```
+pub struct LazyLock<T, F = fn() -> T> {
+    cell: OnceLock<T>,
+    init: UnsafeCell<Option<F>>,
+}
+unsafe impl<T: Send + Sync, F: Send> Sync for LazyLock<T, F> {}
+
+impl<T, F: FnOnce() -> T> LazyLock<T, F> {
+    /// Creates a new lazy value with the given initializing function.
+    pub const fn new(init: F) -> Self {
+        Self {
+            cell: OnceLock::new(),
+            init: UnsafeCell::new(Some(init)),
+        }
+    }
+
+    /// Forces the evaluation of this lazy value and returns a reference to the result.
+    pub fn force(this: &Self) -> &T {
+        this.cell.get_or_init(|| {
+            // SAFETY: The initializer only runs once, so nothing else can be accessing `init`.
+            let init = unsafe { (*this.init.get()).take() };
+            init.expect("LazyLock initializer was already taken")()
+        })
+    }
+}
+
+impl<T, F: FnOnce() -> T> Deref for LazyLock<T, F> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        Self::force(self)
+    }
+}
+
+impl<T: Default> Default for LazyLock<T> {
+    fn default() -> Self {
+        Self::new(T::default)
+    }
+}
+
+impl<T: Debug, F> Debug for LazyLock<T, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut d = f.debug_tuple("LazyLock");
+        match self.cell.get() {
+            Some(value) => d.field(value),
+            None => d.field(&format_args!("<uninit>")),
+        };
+        d.finish()
+    }
+}
@@ -0,0 +1,165 @@
+//! Lock poisoning.
+//!
+//! Panics do not unwind, so a task that panics while holding a lock would normally keep it locked forever.
+//! Instead, the locks held by a task are tracked, and if the task panics they are poisoned and released before
+//! the task is deleted. Locking a poisoned lock returns a [`PoisonError`] containing the guard, so the data
+//! can still be inspected and repaired.
+//!
+//! Only tasks spawned with a [`JoinHandle`](crate::task::JoinHandle) are deleted when they panic, so locks are
+//! only tracked for those tasks. A panic in any other task exits the program.
+
+use alloc::vec::Vec;
+use core::{
+    cell::RefCell,
+    error::Error,
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::os_task_local;
+
+os_task_local! {
+    /// The locks held by the current task, in the order they were acquired.
+    ///
+    /// This is only initialized on tasks that call [`track_held_locks`].
+    static HELD_LOCKS: RefCell<Vec<HeldLock>> = RefCell::new(Vec::new());
+}
+
+struct HeldLock {
+    mutex: pros_sys::mutex_t,
+    poisoned: *const AtomicBool,
+}
+
+/// Starts tracking the locks held by the current task, so that they can be poisoned if it panics.
+///
+/// The tracked locks are freed along with the task's other task locals when it exits.
+pub(crate) fn track_held_locks() {
+    HELD_LOCKS.set(Vec::new());
+}
+
+/// Records that the current task has locked `mutex`, if its locks are tracked.
+pub(crate) fn acquired(mutex: pros_sys::mutex_t, poisoned: &AtomicBool) {
+    HELD_LOCKS.try_with(|held| {
+        held.borrow_mut().push(HeldLock {
+            mutex,
+            poisoned: poisoned as *const AtomicBool,
+        });
+    });
+}
+
+/// Records that the current task has unlocked `mutex`, if its locks are tracked.
+pub(crate) fn released(mutex: pros_sys::mutex_t) {
+    HELD_LOCKS.try_with(|held| {
+        let mut held = held.borrow_mut();
+        if let Some(index) = held.iter().rposition(|lock| lock.mutex == mutex) {
+            held.remove(index);
+        }
+    });
+}
+
+/// Poisons and unlocks every lock held by the current task.
+///
+/// This must only be called by a task that is about to be deleted, since its guards are left dangling.
+pub(crate) fn poison_held_locks() {
+    let held = HELD_LOCKS.try_with(RefCell::take).unwrap_or_default();
+    for lock in held.into_iter().rev() {
+        // SAFETY: The guard that acquired the lock borrows the mutex, and it is never dropped
+        // because the task is about to be deleted, so the mutex must still be alive.
+        unsafe {
+            (*lock.poisoned).store(true, Ordering::Relaxed);
+            pros_sys::mutex_give(lock.mutex);
+        }
+    }
+}
+
+/// A type alias for the result of a lock method which can be poisoned.
+pub type LockResult<Guard> = Result<Guard, PoisonError<Guard>>;
+
+/// A type alias for the result of a nonblocking locking method.
+pub type TryLockResult<Guard> = Result<Guard, TryLockError<Guard>>;
+
+/// An error returned when a lock is acquired after a task panicked while holding it.
+///
+/// The guard is still available through [`PoisonError::into_inner`], so the data can be recovered.
+pub struct PoisonError<T> {
+    guard: T,
+}
+
+impl<T> PoisonError<T> {
+    /// Creates a new `PoisonError` wrapping `guard`.
+    pub const fn new(guard: T) -> Self {
+        Self { guard }
+    }
+
+    /// Consumes the error, returning the guard it contains.
+    pub fn into_inner(self) -> T {
+        self.guard
+    }
+
+    /// Returns a reference to the guard.
+    pub const fn get_ref(&self) -> &T {
+        &self.guard
+    }
+
+    /// Returns a mutable reference to the guard.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T> fmt::Debug for PoisonError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PoisonError").finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for PoisonError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("poisoned lock: another task panicked while holding it")
+    }
+}
+
+impl<T> Error for PoisonError<T> {}
+
+/// An error returned when a lock could not be acquired without blocking, or within a timeout.
+pub enum TryLockError<T> {
+    /// The lock was acquired, but a task panicked while holding it before.
+    Poisoned(PoisonError<T>),
+    /// The lock is held by another task.
+    WouldBlock,
+}
+
+impl<T> From<PoisonError<T>> for TryLockError<T> {
+    fn from(err: PoisonError<T>) -> Self {
+        Self::Poisoned(err)
+    }
+}
+
+impl<T> fmt::Debug for TryLockError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Poisoned(err) => f.debug_tuple("Poisoned").field(err).finish(),
+            Self::WouldBlock => f.write_str("WouldBlock"),
+        }
+    }
+}
+
+impl<T> fmt::Display for TryLockError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Poisoned(err) => err.fmt(f),
+            Self::WouldBlock => f.write_str("lock is held by another task"),
+        }
+    }
+}
+
+impl<T> Error for TryLockError<T> {}
+
+/// Wraps `guard` in a [`PoisonError`] if `poisoned` is `true`.
+pub(crate) const fn map_result<T>(guard: T, poisoned: bool) -> LockResult<T> {
+    if poisoned {
+        Err(PoisonError::new(guard))
+    } else {
+        Ok(guard)
+    }
+}
@@ -0,0 +1,144 @@
+use core::{
+    ffi::c_void,
+    fmt::Debug,
+    marker::PhantomData,
+    mem::{self, ManuallyDrop, MaybeUninit},
+    time::Duration,
+};
+
+use super::timeout_millis;
+use crate::error::take_errno;
+
+/// A fixed-capacity FIFO queue backed by a FreeRTOS queue.
+///
+/// Queues are used to send values between tasks. Sending to a full queue blocks until there is space,
+/// so a task that produces values faster than they can be consumed is slowed down instead of
+/// growing the queue without bound. Values are moved into the queue by copying their bytes,
+/// and are dropped when they are received or when the queue is dropped.
+pub struct Queue<T> {
+    queue: pros_sys::apix::queue_t,
+    _marker: PhantomData<T>,
+}
+unsafe impl<T: Send> Send for Queue<T> {}
+unsafe impl<T: Send> Sync for Queue<T> {}
+
+impl<T> Queue<T> {
+    /// Creates a new queue that can hold up to `capacity` values at once.
+    pub fn new(capacity: u32) -> Self {
+        let queue = unsafe { pros_sys::apix::queue_create(capacity, mem::size_of::<T>() as u32) };
+        if queue.is_null() {
+            panic!("Queue creation failed: {}", take_errno());
+        }
+
+        Self {
+            queue,
+            _marker: PhantomData,
+        }
+    }
+
+    fn append_raw(&self, item: T, timeout: u32) -> Result<(), T> {
+        let item = ManuallyDrop::new(item);
+        let sent = unsafe {
+            pros_sys::apix::queue_append(self.queue, (&*item as *const T).cast::<c_void>(), timeout)
+        };
+
+        if sent {
+            Ok(())
+        } else {
+            Err(ManuallyDrop::into_inner(item))
+        }
+    }
+
+    fn recv_raw(&self, timeout: u32) -> Option<T> {
+        let mut buffer = MaybeUninit::<T>::uninit();
+        let received = unsafe {
+            pros_sys::apix::queue_recv(self.queue, buffer.as_mut_ptr().cast::<c_void>(), timeout)
+        };
+
+        // SAFETY: The queue only contains values that were copied from a valid `T` by `append_raw`,
+        //         and receiving removes the value from the queue, so it will not be read again.
+        received.then(|| unsafe { buffer.assume_init() })
+    }
+
+    /// Sends a value to the back of the queue, blocking the current task until there is space for it.
+    pub fn send(&self, item: T) {
+        if self.append_raw(item, pros_sys::TIMEOUT_MAX).is_err() {
+            panic!("Queue send failed: {}", take_errno());
+        }
+    }
+
+    /// Sends a value to the back of the queue, blocking the current task until there is space for it.
+    ///
+    /// If there is still no space after `timeout` has elapsed, the value is returned in the error.
+    pub fn send_timeout(&self, item: T, timeout: Duration) -> Result<(), T> {
+        self.append_raw(item, timeout_millis(timeout))
+    }
+
+    /// Attempts to send a value to the back of the queue. This function does not block.
+    ///
+    /// If the queue is full, the value is returned in the error.
+    pub fn try_send(&self, item: T) -> Result<(), T> {
+        self.append_raw(item, 0)
+    }
+
+    /// Receives the value at the front of the queue, blocking the current task until one is available.
+    pub fn recv(&self) -> T {
+        self.recv_raw(pros_sys::TIMEOUT_MAX)
+            .unwrap_or_else(|| panic!("Queue receive failed: {}", take_errno()))
+    }
+
+    /// Receives the value at the front of the queue, blocking the current task until one is available.
+    ///
+    /// Returns `None` if no value was sent before `timeout` elapsed.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<T> {
+        self.recv_raw(timeout_millis(timeout))
+    }
+
+    /// Attempts to receive the value at the front of the queue. This function does not block.
+    ///
+    /// Returns `None` if the queue is empty.
+    pub fn try_recv(&self) -> Option<T> {
+        self.recv_raw(0)
+    }
+
+    /// Returns the number of values in the queue.
+    pub fn len(&self) -> usize {
+        unsafe { pros_sys::apix::queue_get_waiting(self.queue) as usize }
+    }
+
+    /// Returns `true` if the queue contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of values that can be sent before the queue is full.
+    pub fn available(&self) -> usize {
+        unsafe { pros_sys::apix::queue_get_available(self.queue) as usize }
+    }
+
+    /// Removes and drops every value in the queue.
+    pub fn clear(&self) {
+        while self.try_recv().is_some() {}
+    }
+}
+
+impl<T> Drop for Queue<T> {
+    fn drop(&mut self) {
+        if mem::needs_drop::<T>() {
+            self.clear();
+        }
+
+        unsafe {
+            pros_sys::apix::queue_delete(self.queue);
+        }
+    }
+}
+
+impl<T> Debug for Queue<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Queue")
+            .field("len", &self.len())
+            .field("available", &self.available())
+            .finish()
+    }
+}
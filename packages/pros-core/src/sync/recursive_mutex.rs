@@ -0,0 +1,161 @@
+use core::{cell::RefCell, fmt::Debug, marker::PhantomData, mem};
+
+use crate::{error::take_errno, task::TaskHandle};
+
+/// A mutex that can be locked multiple times by the task that owns it.
+///
+/// Locking a [`Mutex`](super::Mutex) that the current task already holds deadlocks,
+/// but locking a recursive mutex again from the same task succeeds immediately.
+/// The mutex is released once every guard has been dropped.
+///
+/// Because several guards may exist at once on the owning task, the guards only give shared access to the data.
+/// The data is wrapped in a [`RefCell`] so that it can still be mutated, which panics if it is
+/// mutably borrowed through one guard while it is already borrowed through another.
+///
+/// # Poisoning
+///
+/// Unlike [`Mutex`](super::Mutex), a recursive mutex is not tracked or poisoned. If a task spawned with a
+/// [`JoinHandle`](crate::task::JoinHandle) panics while holding the lock, the lock is never released.
+pub struct RecursiveMutex<T> {
+    pros_mutex: pros_sys::mutex_t,
+    data: Option<RefCell<T>>,
+}
+unsafe impl<T: Send> Send for RecursiveMutex<T> {}
+unsafe impl<T: Send> Sync for RecursiveMutex<T> {}
+
+impl<T> RecursiveMutex<T> {
+    /// Creates a new recursive mutex.
+    pub fn new(data: T) -> Self {
+        let pros_mutex = unsafe { pros_sys::apix::mutex_recursive_create() };
+        if pros_mutex.is_null() {
+            panic!("Recursive mutex creation failed: {}", take_errno());
+        }
+
+        Self {
+            pros_mutex,
+            data: Some(RefCell::new(data)),
+        }
+    }
+
+    /// Locks the mutex so that it cannot be locked in another task at the same time.
+    /// Blocks the current task until the lock is acquired.
+    ///
+    /// If the current task already holds the lock, this returns immediately.
+    pub fn lock(&self) -> RecursiveMutexGuard<'_, T> {
+        if !unsafe { pros_sys::apix::mutex_recursive_take(self.pros_mutex, pros_sys::TIMEOUT_MAX) }
+        {
+            panic!("Recursive mutex lock failed: {}", take_errno());
+        }
+
+        RecursiveMutexGuard::new(self)
+    }
+
+    /// Attempts to acquire this lock. This function does not block.
+    pub fn try_lock(&self) -> Option<RecursiveMutexGuard<'_, T>> {
+        let success = unsafe { pros_sys::apix::mutex_recursive_take(self.pros_mutex, 0) };
+        success.then(|| RecursiveMutexGuard::new(self))
+    }
+
+    /// Returns the task that currently holds the lock, or `None` if it isn't locked.
+    pub fn owner(&self) -> Option<TaskHandle> {
+        let task = unsafe { pros_sys::apix::mutex_get_owner(self.pros_mutex) };
+        (!task.is_null()).then_some(TaskHandle { task })
+    }
+
+    /// Returns `true` if the current task holds the lock.
+    pub fn is_owned_by_current_task(&self) -> bool {
+        self.owner() == Some(crate::task::current())
+    }
+
+    /// Consumes the mutex and returns the inner data.
+    pub fn into_inner(mut self) -> T {
+        let data = mem::take(&mut self.data).unwrap();
+        data.into_inner()
+    }
+
+    /// Gets a mutable reference to the inner data.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.as_mut().unwrap().get_mut()
+    }
+}
+
+impl<T> Drop for RecursiveMutex<T> {
+    fn drop(&mut self) {
+        unsafe {
+            pros_sys::mutex_delete(self.pros_mutex);
+        }
+    }
+}
+
+impl<T> Debug for RecursiveMutex<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        struct Placeholder;
+        impl Debug for Placeholder {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.write_str("<locked>")
+            }
+        }
+
+        let mut d = f.debug_struct("RecursiveMutex");
+        match self.try_lock() {
+            Some(guard) => match guard.try_borrow() {
+                Ok(data) => d.field("data", &&*data),
+                Err(_) => d.field("data", &Placeholder),
+            },
+            None => d.field("data", &Placeholder),
+        };
+        d.field("owner", &self.owner());
+        d.finish_non_exhaustive()
+    }
+}
+
+impl<T> Default for RecursiveMutex<T>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for RecursiveMutex<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Allows the user to access the data from a locked recursive mutex.
+/// Dereference to get the [`RefCell`] containing the inner data.
+#[derive(Debug)]
+pub struct RecursiveMutexGuard<'a, T> {
+    mutex: &'a RecursiveMutex<T>,
+    // The lock must be released by the task that took it.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<'a, T> RecursiveMutexGuard<'a, T> {
+    const fn new(mutex: &'a RecursiveMutex<T>) -> Self {
+        Self {
+            mutex,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl<T> core::ops::Deref for RecursiveMutexGuard<'_, T> {
+    type Target = RefCell<T>;
+    fn deref(&self) -> &RefCell<T> {
+        self.mutex.data.as_ref().unwrap()
+    }
+}
+
+impl<T> Drop for RecursiveMutexGuard<'_, T> {
+    fn drop(&mut self) {
+        unsafe {
+            pros_sys::apix::mutex_recursive_give(self.mutex.pros_mutex);
+        }
+    }
+}
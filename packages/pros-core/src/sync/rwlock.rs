@@ -0,0 +1,183 @@
+use core::{cell::UnsafeCell, fmt::Debug};
+
+use super::{Condvar, Mutex, PoisonError};
+
+/// A reader-writer lock.
+///
+/// Any number of readers or a single writer may hold the lock at once.
+/// Writers are preferred: once a task starts waiting to write, new readers wait until it is done,
+/// so a constant stream of readers cannot starve a writer.
+///
+/// # Poisoning
+///
+/// Unlike [`Mutex`](super::Mutex), a reader-writer lock is not tracked or poisoned. If a task spawned with a
+/// [`JoinHandle`](crate::task::JoinHandle) panics while holding the lock, the lock is never released.
+pub struct RwLock<T> {
+    state: Mutex<RwLockState>,
+    changed: Condvar,
+    data: UnsafeCell<T>,
+}
+unsafe impl<T: Send> Send for RwLock<T> {}
+unsafe impl<T: Send + Sync> Sync for RwLock<T> {}
+
+#[derive(Debug, Default)]
+struct RwLockState {
+    readers: usize,
+    writer: bool,
+    waiting_writers: usize,
+}
+
+impl RwLockState {
+    const fn can_read(&self) -> bool {
+        !self.writer && self.waiting_writers == 0
+    }
+
+    const fn can_write(&self) -> bool {
+        !self.writer && self.readers == 0
+    }
+}
+
+impl<T> RwLock<T> {
+    /// Creates a new reader-writer lock.
+    pub fn new(data: T) -> Self {
+        Self {
+            state: Mutex::new(RwLockState::default()),
+            changed: Condvar::new(),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// Locks the lock for reading, blocking the current task until it can be shared with other readers.
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        let state = self.state.lock_unpoisoned();
+        let mut state = self
+            .changed
+            .wait_while(state, |state| !state.can_read())
+            .unwrap_or_else(PoisonError::into_inner);
+        state.readers += 1;
+
+        RwLockReadGuard { lock: self }
+    }
+
+    /// Locks the lock for writing, blocking the current task until it can be held exclusively.
+    pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+        let mut state = self.state.lock_unpoisoned();
+        state.waiting_writers += 1;
+        let mut state = self
+            .changed
+            .wait_while(state, |state| !state.can_write())
+            .unwrap_or_else(PoisonError::into_inner);
+        state.waiting_writers -= 1;
+        state.writer = true;
+
+        RwLockWriteGuard { lock: self }
+    }
+
+    /// Attempts to lock the lock for reading. This function does not block.
+    pub fn try_read(&self) -> Option<RwLockReadGuard<'_, T>> {
+        let mut state = self.state.lock_unpoisoned();
+        state.can_read().then(|| {
+            state.readers += 1;
+            RwLockReadGuard { lock: self }
+        })
+    }
+
+    /// Attempts to lock the lock for writing. This function does not block.
+    pub fn try_write(&self) -> Option<RwLockWriteGuard<'_, T>> {
+        let mut state = self.state.lock_unpoisoned();
+        state.can_write().then(|| {
+            state.writer = true;
+            RwLockWriteGuard { lock: self }
+        })
+    }
+
+    /// Consumes the lock and returns the inner data.
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+
+    /// Gets a mutable reference to the inner data.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+}
+
+impl<T> Debug for RwLock<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut d = f.debug_struct("RwLock");
+        match self.try_read() {
+            Some(guard) => d.field("data", &&*guard),
+            None => d.field("data", &format_args!("<locked>")),
+        };
+        d.finish_non_exhaustive()
+    }
+}
+
+impl<T> Default for RwLock<T>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for RwLock<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Allows the user to read the data from an [`RwLock`] locked for reading.
+/// Dereference to get the inner data.
+#[derive(Debug)]
+pub struct RwLockReadGuard<'a, T> {
+    lock: &'a RwLock<T>,
+}
+
+impl<T> core::ops::Deref for RwLockReadGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for RwLockReadGuard<'_, T> {
+    fn drop(&mut self) {
+        let mut state = self.lock.state.lock_unpoisoned();
+        state.readers -= 1;
+        if state.readers == 0 {
+            self.lock.changed.notify_all();
+        }
+    }
+}
+
+/// Allows the user to access the data from an [`RwLock`] locked for writing.
+/// Dereference to get the inner data.
+#[derive(Debug)]
+pub struct RwLockWriteGuard<'a, T> {
+    lock: &'a RwLock<T>,
+}
+
+impl<T> core::ops::Deref for RwLockWriteGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> core::ops::DerefMut for RwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for RwLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.state.lock_unpoisoned().writer = false;
+        self.lock.changed.notify_all();
+    }
+}
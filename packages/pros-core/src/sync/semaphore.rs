@@ -0,0 +1,91 @@
+use core::{fmt::Debug, time::Duration};
+
+use super::timeout_millis;
+use crate::error::take_errno;
+
+/// A FreeRTOS semaphore.
+///
+/// Semaphores keep a count of available resources. [`Semaphore::wait`] blocks the current task until
+/// the count is greater than zero and then decrements it, and [`Semaphore::post`] increments it.
+/// Unlike a [`Mutex`](super::Mutex), a semaphore may be posted by a different task than the one that waited on it,
+/// which makes binary semaphores useful for signalling events between tasks.
+pub struct Semaphore {
+    sem: pros_sys::apix::sem_t,
+}
+unsafe impl Send for Semaphore {}
+unsafe impl Sync for Semaphore {}
+
+impl Semaphore {
+    /// Creates a new counting semaphore that can be posted up to `max_count` times,
+    /// with an initial count of `initial_count`.
+    pub fn new(max_count: u32, initial_count: u32) -> Self {
+        let sem = unsafe { pros_sys::apix::sem_create(max_count, initial_count) };
+        if sem.is_null() {
+            panic!("Semaphore creation failed: {}", take_errno());
+        }
+
+        Self { sem }
+    }
+
+    /// Creates a new binary semaphore.
+    ///
+    /// Binary semaphores have a maximum count of one and start out empty,
+    /// so the first call to [`Semaphore::wait`] blocks until the semaphore is posted.
+    pub fn binary() -> Self {
+        let sem = unsafe { pros_sys::apix::sem_binary_create() };
+        if sem.is_null() {
+            panic!("Semaphore creation failed: {}", take_errno());
+        }
+
+        Self { sem }
+    }
+
+    /// Blocks the current task until the semaphore's count is greater than zero, then decrements it.
+    pub fn wait(&self) {
+        if !unsafe { pros_sys::apix::sem_wait(self.sem, pros_sys::TIMEOUT_MAX) } {
+            panic!("Semaphore wait failed: {}", take_errno());
+        }
+    }
+
+    /// Blocks the current task until the semaphore's count is greater than zero, then decrements it.
+    ///
+    /// Returns `false` if the count did not become greater than zero before `timeout` elapsed.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        unsafe { pros_sys::apix::sem_wait(self.sem, timeout_millis(timeout)) }
+    }
+
+    /// Decrements the semaphore's count if it is greater than zero. This function does not block.
+    ///
+    /// Returns `false` if the count was zero.
+    pub fn try_wait(&self) -> bool {
+        unsafe { pros_sys::apix::sem_wait(self.sem, 0) }
+    }
+
+    /// Increments the semaphore's count, waking a task that is waiting on it.
+    ///
+    /// Returns `false` if the count is already at its maximum.
+    pub fn post(&self) -> bool {
+        unsafe { pros_sys::apix::sem_post(self.sem) }
+    }
+
+    /// Returns the semaphore's current count.
+    pub fn count(&self) -> u32 {
+        unsafe { pros_sys::apix::sem_get_count(self.sem) }
+    }
+}
+
+impl Drop for Semaphore {
+    fn drop(&mut self) {
+        unsafe {
+            pros_sys::apix::sem_delete(self.sem);
+        }
+    }
+}
+
+impl Debug for Semaphore {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Semaphore")
+            .field("count", &self.count())
+            .finish()
+    }
+}
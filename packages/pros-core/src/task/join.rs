@@ -0,0 +1,325 @@
+//! Tasks that return a value when they finish.
+
+use alloc::{boxed::Box, string::String, sync::Arc};
+use core::{cell::RefCell, time::Duration};
+
+use snafu::Snafu;
+
+use super::{scope::ScopeData, TaskHandle};
+use crate::{
+    os_task_local,
+    sync::{Mutex, Semaphore},
+};
+
+os_task_local! {
+    /// Called when the current task panics, if it was spawned with a [`JoinHandle`].
+    static PANIC_HOOK: RefCell<Option<Box<dyn FnOnce(String)>>> = RefCell::new(None);
+
+    /// The scopes that the current task is inside of, if it was spawned with a [`JoinHandle`].
+    static ACTIVE_SCOPES: RefCell<Option<Arc<Mutex<ActiveScopes>>>> = RefCell::new(None);
+}
+
+/// The number of [`scope`](super::scope)s that a joinable task is inside of.
+///
+/// Scoped tasks borrow from the stack of the task that created the scope,
+/// so that task must not be deleted until every scope it is inside of has ended.
+#[derive(Default)]
+pub(crate) struct ActiveScopes {
+    count: usize,
+    /// Set when the task is aborted inside a scope, so that it deletes itself once the scope ends.
+    abort_requested: bool,
+}
+
+/// Returns `true` if the current task is inside a scope whose tasks may still be running.
+fn in_scope() -> bool {
+    ACTIVE_SCOPES
+        .try_with(|scopes| {
+            scopes
+                .borrow()
+                .as_ref()
+                .is_some_and(|scopes| scopes.lock_unpoisoned().count > 0)
+        })
+        .unwrap_or(false)
+}
+
+/// Marks the current task as being inside a scope until the guard is dropped.
+///
+/// If the task was aborted while inside the scope, dropping the guard of its outermost scope deletes it.
+pub(crate) struct ScopeGuard {
+    scopes: Option<Arc<Mutex<ActiveScopes>>>,
+}
+
+impl ScopeGuard {
+    pub(crate) fn enter() -> Self {
+        let scopes = ACTIVE_SCOPES
+            .try_with(|scopes| scopes.borrow().clone())
+            .flatten();
+        if let Some(scopes) = &scopes {
+            scopes.lock_unpoisoned().count += 1;
+        }
+        Self { scopes }
+    }
+}
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        let Some(scopes) = self.scopes.take() else {
+            return;
+        };
+
+        let mut state = scopes.lock_unpoisoned();
+        state.count -= 1;
+        let aborted = state.count == 0 && state.abort_requested;
+        drop(state);
+        drop(scopes);
+
+        if aborted {
+            // The handle has already stored the result, so the task only needs to be deleted.
+            crate::sync::poison_held_locks();
+            super::monitor::deregister(&super::current());
+            super::local::free_storage();
+            unsafe {
+                pros_sys::task_delete(core::ptr::null());
+            }
+        }
+    }
+}
+
+/// Notifies the [`JoinHandle`] of the current task that it panicked, and then deletes the task.
+///
+/// This is called by the panic handler and only returns if the current task has no [`JoinHandle`]
+/// or is inside a [`scope`](super::scope), in which case the panic should be handled as usual.
+#[doc(hidden)]
+pub fn __handle_panic(message: String) {
+    // Scoped tasks may still be borrowing from the stack of this task, so it must not be deleted.
+    if in_scope() {
+        return;
+    }
+
+    let Some(hook) = PANIC_HOOK.take() else {
+        return;
+    };
+
+    // Release the task's locks before running the hook, since the hook may need them.
+    crate::sync::poison_held_locks();
+    hook(message);
+
+    super::monitor::deregister(&super::current());
+    super::local::free_storage();
+    unsafe {
+        pros_sys::task_delete(core::ptr::null());
+    }
+
+    // The task has been deleted, so this is never reached.
+    loop {
+        super::delay(Duration::from_secs(1));
+    }
+}
+
+/// Errors that can occur when joining a task.
+#[derive(Debug, Snafu)]
+pub enum JoinError {
+    /// The task panicked: {message}
+    Panicked {
+        /// The panic message, including where the panic occurred.
+        message: String,
+    },
+    /// The task was aborted before it finished.
+    Aborted,
+}
+
+pub(crate) struct Packet<T> {
+    result: Mutex<Option<Result<T, JoinError>>>,
+    finished: Semaphore,
+    scopes: Arc<Mutex<ActiveScopes>>,
+}
+
+impl<T> Packet<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            result: Mutex::new(None),
+            finished: Semaphore::binary(),
+            scopes: Arc::default(),
+        }
+    }
+
+    /// Stores the result of the task, unless it has already finished.
+    fn finish(&self, result: Result<T, JoinError>) {
+        let mut slot = self.result.lock_unpoisoned();
+        if slot.is_none() {
+            *slot = Some(result);
+            drop(slot);
+            self.finished.post();
+        }
+    }
+}
+
+impl<T: Send> Packet<T> {
+    /// Wraps `f` so that its return value or panic is stored in the packet.
+    pub(crate) fn wrap<F>(self: &Arc<Self>, f: F) -> Box<dyn FnOnce() + Send>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: 'static,
+    {
+        // SAFETY: `f` and `T` are `'static`, so they can't be used after being freed.
+        unsafe { self.wrap_unchecked(f, None) }
+    }
+
+    /// Wraps `f` so that its return value or panic is stored in the packet, erasing its lifetime.
+    ///
+    /// If a scope is given, the task is marked as finished in the scope after the task
+    /// has dropped its reference to the packet.
+    ///
+    /// # Safety
+    ///
+    /// `'a` must outlive the task running the returned function.
+    pub(crate) unsafe fn wrap_unchecked<'a, F>(
+        self: &Arc<Self>,
+        f: F,
+        scope: Option<Arc<ScopeData>>,
+    ) -> Box<dyn FnOnce() + Send>
+    where
+        F: FnOnce() -> T + Send + 'a,
+        T: 'a,
+    {
+        let packet = self.clone();
+
+        let main: Box<dyn FnOnce() + Send + 'a> = Box::new(move || {
+            crate::sync::track_held_locks();
+            ACTIVE_SCOPES.set(Some(packet.scopes.clone()));
+
+            let hook_packet = packet.clone();
+            let hook_scope = scope.clone();
+            let hook: Box<dyn FnOnce(String) + 'a> = Box::new(move |message| {
+                if let Some(scope) = &hook_scope {
+                    scope.task_panicked();
+                }
+                hook_packet.finish(Err(JoinError::Panicked { message }));
+                drop(hook_packet);
+                if let Some(scope) = hook_scope {
+                    scope.task_finished();
+                }
+            });
+            // SAFETY: The hook either runs or is dropped before the task finishes, so it can't outlive `'a`.
+            PANIC_HOOK.set(Some(unsafe { core::mem::transmute(hook) }));
+
+            let output = f();
+
+            PANIC_HOOK.set(None);
+            packet.finish(Ok(output));
+            drop(packet);
+            if let Some(scope) = scope {
+                scope.task_finished();
+            }
+        });
+
+        // SAFETY: The caller guarantees that `'a` outlives the task.
+        unsafe {
+            core::mem::transmute::<Box<dyn FnOnce() + Send + 'a>, Box<dyn FnOnce() + Send>>(main)
+        }
+    }
+}
+
+/// An owned permission to join on a task, getting its return value.
+///
+/// Created with [`spawn_joinable`](super::spawn_joinable) or [`Builder::spawn_joinable`](super::Builder::spawn_joinable).
+/// Dropping the handle detaches the task; it keeps running, but its return value is dropped.
+///
+/// # Panics
+///
+/// If the task panics, the panic message is printed and the task is deleted instead of exiting the program.
+/// The panic can then be observed by joining the task. Because panics do not unwind, anything owned by the
+/// task at the time of the panic is leaked. Any [`Mutex`]es it was holding are poisoned and released.
+///
+/// If the task panics inside a [`scope`](super::scope), the program exits instead,
+/// since the scoped tasks may still be borrowing from its stack.
+pub struct JoinHandle<T> {
+    task: TaskHandle,
+    packet: Arc<Packet<T>>,
+}
+
+impl<T> JoinHandle<T> {
+    pub(crate) const fn new(task: TaskHandle, packet: Arc<Packet<T>>) -> Self {
+        Self { task, packet }
+    }
+
+    /// Returns the handle of the underlying task.
+    ///
+    /// Aborting the task through this handle will cause joining it to block forever.
+    /// Use [`JoinHandle::abort`] instead.
+    pub const fn task(&self) -> &TaskHandle {
+        &self.task
+    }
+
+    /// Returns `true` if the task has finished, either by returning or by panicking.
+    pub fn is_finished(&self) -> bool {
+        self.packet.result.lock_unpoisoned().is_some()
+    }
+
+    /// Waits for the task to finish, and then returns its return value.
+    ///
+    /// Returns an error if the task panicked or was aborted.
+    pub fn join(self) -> Result<T, JoinError> {
+        self.packet.finished.wait();
+        self.take_result()
+    }
+
+    /// Waits for the task to finish for no longer than `timeout`, and then returns its return value.
+    ///
+    /// If the task has not finished before `timeout` elapses, the handle is returned in the error
+    /// so that the caller can keep waiting for it or abort it.
+    pub fn join_timeout(self, timeout: Duration) -> Result<Result<T, JoinError>, Self> {
+        if self.packet.finished.wait_timeout(timeout) {
+            Ok(self.take_result())
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Aborts the task if it has not finished yet. Memory allocated by the task will not be freed.
+    ///
+    /// Joining the task after aborting it returns [`JoinError::Aborted`].
+    ///
+    /// If the task is inside a [`scope`](super::scope), it keeps running until the scope ends
+    /// and is deleted then, since the scoped tasks borrow from its stack.
+    ///
+    /// Otherwise the task is deleted immediately. Unlike a panic, this does not poison or release
+    /// any [`Mutex`](crate::sync::Mutex)es the task is holding, so they stay locked forever,
+    /// and the task's [task-locals](crate::os_task_local!) are leaked without being dropped.
+    pub fn abort(&self) {
+        let mut result = self.packet.result.lock_unpoisoned();
+        if result.is_none() {
+            let mut scopes = self.packet.scopes.lock_unpoisoned();
+            if scopes.count > 0 {
+                scopes.abort_requested = true;
+            } else {
+                // The task can't finish while the result is locked, so it is still safe to delete.
+                super::monitor::deregister(&self.task);
+                unsafe {
+                    pros_sys::task_delete(self.task.task);
+                }
+            }
+            drop(scopes);
+            *result = Some(Err(JoinError::Aborted));
+            drop(result);
+            self.packet.finished.post();
+        }
+    }
+
+    fn take_result(&self) -> Result<T, JoinError> {
+        self.packet
+            .result
+            .lock_unpoisoned()
+            .take()
+            .expect("task finished without a result")
+    }
+}
+
+impl<T> core::fmt::Debug for JoinHandle<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("JoinHandle")
+            .field("task", &self.task)
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
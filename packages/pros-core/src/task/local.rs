@@ -5,6 +5,7 @@
 //! This sacrifices a bit of speed for the ability to have as many entries as memory allows.
 //!
 //! [`LocalKey`]s can be created with the [`os_task_local!`](crate::os_task_local!) macro.
+//! Their values are dropped when the task exits, unless it is aborted.
 //! ## Example
 //! ```rust
 //! os_task_local! {
@@ -50,6 +51,12 @@ unsafe fn thread_local_storage_get<T>(task: pros_sys::task_t, index: u32) -> Opt
     }
 }
 
+/// Get the [`ThreadLocalStorage`] for the current task, if it has been created.
+fn existing_storage() -> Option<&'static RefCell<ThreadLocalStorage>> {
+    // SAFETY: This is safe as long as index 0 of the freeRTOS TLS is never set to any other type.
+    unsafe { thread_local_storage_get(current().task, 0) }
+}
+
 /// Get or create the [`ThreadLocalStorage`] for the current task.
 fn fetch_storage() -> &'static RefCell<ThreadLocalStorage> {
     let current = current();
@@ -68,11 +75,47 @@ fn fetch_storage() -> &'static RefCell<ThreadLocalStorage> {
     }
 }
 
+/// Drops every task local value of the current task, and frees its storage.
+///
+/// This is called when a task exits. It must not be called while any values are borrowed.
+pub(crate) fn free_storage() {
+    let current = current();
+
+    // Destructors may access task locals again, which creates new storage, so this repeats until none is left.
+    // SAFETY: This is safe as long as index 0 of the freeRTOS TLS is never set to any other type.
+    while let Some(storage) =
+        unsafe { thread_local_storage_get::<RefCell<ThreadLocalStorage>>(current.task, 0) }
+    {
+        unsafe {
+            pros_sys::vTaskSetThreadLocalStoragePointer(current.task, 0, core::ptr::null());
+            // SAFETY: The storage was leaked from a box in `fetch_storage`, and has been removed from TLS.
+            let storage = Box::from_raw((storage as *const RefCell<ThreadLocalStorage>).cast_mut());
+            for value in storage.into_inner().data.into_values() {
+                (value.drop)(value.ptr);
+            }
+        }
+    }
+}
+
 /// A custom thread local storage implementation.
 /// This itself is stored inside real OS TLS, it allows for more than 5 entries in TLS.
 /// [`LocalKey`]s store their data inside this struct.
 struct ThreadLocalStorage {
-    pub data: BTreeMap<usize, NonNull<()>>,
+    pub data: BTreeMap<usize, Value>,
+}
+
+/// A type-erased, boxed task local value.
+#[derive(Clone, Copy)]
+struct Value {
+    ptr: NonNull<()>,
+    /// Drops the box that `ptr` points to.
+    drop: unsafe fn(NonNull<()>),
+}
+
+/// Drops a `Box<T>` that was leaked as `ptr`.
+unsafe fn drop_value<T>(ptr: NonNull<()>) {
+    // SAFETY: The caller guarantees that `ptr` was leaked from a `Box<T>`.
+    drop(unsafe { Box::from_raw(ptr.cast::<T>().as_ptr()) });
 }
 
 /// A TLS key that owns its data.
@@ -101,11 +144,26 @@ impl<T: 'static> LocalKey<T> {
 
     /// Passes a reference to the value of this key to the given closure.
     /// If the value has not been initialized yet, it will be initialized.
+    ///
+    /// The value is dropped when the task exits, so the reference can't outlive the closure.
     pub fn with<F, R>(&'static self, f: F) -> R
     where
-        F: FnOnce(&'static T) -> R,
+        F: FnOnce(&T) -> R,
     {
-        self.initialize_with((self.init)(), |_, val| f(val))
+        self.initialize_with(self.init, |_, val| f(val))
+    }
+
+    /// Passes a reference to the value of this key to the given closure if it has already been
+    /// initialized on the current task, without initializing it.
+    ///
+    /// Returns `None` if the value has not been initialized.
+    pub fn try_with<F, R>(&'static self, f: F) -> Option<R>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        let storage = existing_storage()?;
+        let val = storage.borrow().data.get(self.index()).copied()?;
+        Some(f(unsafe { val.ptr.cast().as_ref() }))
     }
 
     /// Acquires a reference to the value in this TLS key, initializing it with
@@ -114,23 +172,29 @@ impl<T: 'static> LocalKey<T> {
     /// If `init` was used to initialize the task local variable, `None` is
     /// passed as the first argument to `f`. If it was already initialized,
     /// `Some(init)` is passed to `f`.
-    fn initialize_with<F, R>(&'static self, init: T, f: F) -> R
+    fn initialize_with<I, F, R>(&'static self, init: I, f: F) -> R
     where
-        F: FnOnce(Option<T>, &'static T) -> R,
+        I: FnOnce() -> T,
+        F: FnOnce(Option<I>, &T) -> R,
     {
         let storage = fetch_storage();
         let index = *self.index();
 
-        if let Some(val) = storage.borrow().data.get(&index) {
-            return f(Some(init), unsafe { val.cast().as_ref() });
+        // The storage must not be borrowed while calling `f` or `init`, since they may access other keys.
+        let val = storage.borrow().data.get(&index).copied();
+        if let Some(val) = val {
+            return f(Some(init), unsafe { val.ptr.cast().as_ref() });
         }
 
-        let val = Box::leak(Box::new(init));
-        storage
-            .borrow_mut()
-            .data
-            .insert(index, NonNull::new((val as *mut T).cast::<()>()).unwrap());
-        f(None, val)
+        let val = NonNull::from(Box::leak(Box::new(init())));
+        storage.borrow_mut().data.insert(
+            index,
+            Value {
+                ptr: val.cast(),
+                drop: drop_value::<T>,
+            },
+        );
+        f(None, unsafe { val.as_ref() })
     }
 }
 
@@ -140,14 +204,17 @@ impl<T: 'static> LocalKey<Cell<T>> {
     /// If the value was already initialized, it is overwritten.
     /// If the value was not initialized, it is initialized with `value`.
     pub fn set(&'static self, value: T) {
-        self.initialize_with(Cell::new(value), |value, cell| {
-            if let Some(value) = value {
-                // The cell was already initialized, so `value` wasn't used to
-                // initialize it. So we overwrite the current value with the
-                // new one instead.
-                cell.set(value.into_inner());
-            }
-        });
+        self.initialize_with(
+            || Cell::new(value),
+            |init, cell| {
+                if let Some(init) = init {
+                    // The cell was already initialized, so `init` wasn't used to
+                    // initialize it. So we overwrite the current value with the
+                    // new one instead.
+                    cell.set(init().into_inner());
+                }
+            },
+        );
     }
 
     /// Gets a copy of the value in this TLS key.
@@ -203,14 +270,17 @@ impl<T: 'static> LocalKey<RefCell<T>> {
     ///
     /// Panics if the value is currently borrowed.
     pub fn set(&'static self, value: T) {
-        self.initialize_with(RefCell::new(value), |value, cell| {
-            if let Some(value) = value {
-                // The cell was already initialized, so `value` wasn't used to
-                // initialize it. So we overwrite the current value with the
-                // new one instead.
-                *cell.borrow_mut() = value.into_inner();
-            }
-        });
+        self.initialize_with(
+            || RefCell::new(value),
+            |init, cell| {
+                if let Some(init) = init {
+                    // The cell was already initialized, so `init` wasn't used to
+                    // initialize it. So we overwrite the current value with the
+                    // new one instead.
+                    *cell.borrow_mut() = init().into_inner();
+                }
+            },
+        );
     }
 
     /// Takes the value out of this TLS key, replacing it with the [`Default`] value.
@@ -5,6 +5,8 @@
 //! A handle to the current task can be obtained with [`current`].
 //!
 //! Tasks can be created with the [`spawn`] function or, for more control, with a task [`Builder`].
+//! To get a value back from a task, spawn it with [`spawn_joinable`] and [`join`](JoinHandle::join) the returned [`JoinHandle`].
+//! Tasks that need to borrow data from the stack can be spawned in a [`scope`].
 //! ## Example
 //! ```rust
 //! # use pros::prelude::println;
@@ -17,14 +19,25 @@
 //! Task locals can be created with the [`os_task_local!`](crate::os_task_local!) macro.
 //! See the [`local`] module for more info on the custom task local implementation used.
 
+mod join;
 pub mod local;
+mod monitor;
+mod notify;
+mod scope;
 
 use alloc::{
     boxed::Box,
     string::{String, ToString},
+    sync::Arc,
 };
 use core::{ffi::CStr, hash::Hash, str::Utf8Error, time::Duration};
 
+#[doc(hidden)]
+pub use join::__handle_panic;
+pub use join::{JoinError, JoinHandle};
+pub use monitor::{running_tasks, MonitorReport, TaskInfo, TaskMonitor, TaskUsage};
+pub use notify::{take_notification, wait_notification, NotifyAction, NotifyResult};
+pub use scope::{scope, Scope, ScopedJoinHandle};
 use snafu::Snafu;
 
 use crate::{bail_on, map_errno};
@@ -40,6 +53,19 @@ where
     Builder::new().spawn(f).expect("Failed to spawn task")
 }
 
+/// Creates a task that returns a value, which can be retrieved by joining the returned [`JoinHandle`].
+///
+/// See [`spawn`] for more information about tasks.
+pub fn spawn_joinable<F, T>(f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    Builder::new()
+        .spawn_joinable(f)
+        .expect("Failed to spawn task")
+}
+
 /// Low level task spawning functionality
 fn spawn_inner<F: FnOnce() + Send + 'static>(
     function: F,
@@ -75,6 +101,7 @@ pub struct TaskHandle {
     pub(crate) task: pros_sys::task_t,
 }
 unsafe impl Send for TaskHandle {}
+unsafe impl Sync for TaskHandle {}
 impl Hash for TaskHandle {
     fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
         self.task.hash(state)
@@ -112,12 +139,27 @@ impl TaskHandle {
         }
     }
 
+    /// Gets the priority of the task.
+    pub fn priority(&self) -> u32 {
+        unsafe { pros_sys::task_get_priority(self.task) }
+    }
+
+    /// Gets the smallest amount of free stack space the task has had since it started, in bytes.
+    ///
+    /// If this gets close to zero, the task is at risk of overflowing its stack
+    /// and should be spawned with a larger [`TaskStackDepth`].
+    pub fn stack_high_water_mark(&self) -> usize {
+        unsafe { pros_sys::apix::uxTaskGetStackHighWaterMark(self.task) as usize * 4 }
+    }
+
     /// Get the state of the task.
     pub fn state(&self) -> TaskState {
         unsafe { pros_sys::task_get_state(self.task).into() }
     }
 
     /// Send a notification to the task.
+    ///
+    /// This increments the task's notification value. See [`TaskHandle::notify_with`] for other actions.
     pub fn notify(&self) {
         unsafe {
             pros_sys::task_notify(self.task);
@@ -132,7 +174,12 @@ impl TaskHandle {
     }
 
     /// Aborts the task and consumes it. Memory allocated by the task will not be freed.
+    ///
+    /// Unlike a panic, this does not poison or release any [`Mutex`](crate::sync::Mutex)es the task
+    /// is holding, so they stay locked forever, and the task's [task-locals](crate::os_task_local!)
+    /// are leaked without being dropped.
     pub fn abort(self) {
+        monitor::deregister(&self);
         unsafe {
             pros_sys::task_delete(self.task);
         }
@@ -193,10 +240,22 @@ impl<'a> Builder<'a> {
             self.name,
         )
     }
+
+    /// Builds and spawns a task that returns a value, which can be retrieved by joining the returned [`JoinHandle`].
+    pub fn spawn_joinable<F, T>(self, function: F) -> Result<JoinHandle<T>, SpawnError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let packet = Arc::new(join::Packet::new());
+        let task = self.spawn(packet.wrap(function))?;
+
+        Ok(JoinHandle::new(task, packet))
+    }
 }
 
 /// Represents the current state of a task.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TaskState {
     /// The task is currently utilizing the processor
     Running,
@@ -273,7 +332,11 @@ where
         // SAFETY: caller must ensure `this` is an owned `TaskEntrypoint<F>` on the heap
         let this = unsafe { Box::from_raw(this.cast::<Self>()) };
 
-        (this.function)()
+        let task = current();
+        monitor::register(task.clone());
+        (this.function)();
+        monitor::deregister(&task);
+        local::free_storage();
     }
 }
 
@@ -332,6 +395,22 @@ impl Interval {
     }
 }
 
+/// Returns the number of tasks the kernel is managing, including tasks not created by pros-rs.
+///
+/// Tasks that have been deleted but not cleaned up yet are also included.
+pub fn count() -> u32 {
+    unsafe { pros_sys::task_get_count() }
+}
+
+/// Finds a task by its name.
+///
+/// This searches every task the kernel is managing, so it is relatively slow and should be used sparingly.
+pub fn find_by_name(name: &str) -> Option<TaskHandle> {
+    let name = alloc::ffi::CString::new(name).ok()?;
+    let task = unsafe { pros_sys::task_get_by_name(name.as_ptr()) };
+    (!task.is_null()).then_some(TaskHandle { task })
+}
+
 /// Returns the task the function was called from.
 pub fn current() -> TaskHandle {
     unsafe {
@@ -342,6 +421,7 @@ pub fn current() -> TaskHandle {
 
 /// Gets the first notification in the queue.
 /// If there is none, blocks until a notification is received.
+/// To wait with a timeout, use [`wait_notification`] or [`take_notification`].
 /// I am unsure what happens if the thread is unblocked while waiting.
 /// returns the value of the notification
 pub fn get_notification() -> u32 {
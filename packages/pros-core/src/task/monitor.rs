@@ -0,0 +1,280 @@
+//! Task introspection and CPU usage monitoring.
+
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use core::{
+    ffi::CStr,
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use super::{delay, suspend_all, Builder, TaskHandle, TaskPriority, TaskState};
+use crate::sync::{LazyLock, Mutex};
+
+/// Tasks spawned by pros-rs that have started and not yet finished.
+static REGISTRY: LazyLock<Mutex<Vec<TaskHandle>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+/// Tasks created by PROS that are included in [`TaskMonitor`] reports, if they exist.
+const PROS_TASKS: [&CStr; 6] = [
+    c"PROS System Daemon",
+    c"User Initialization (PROS)",
+    c"User Comp. Init (PROS)",
+    c"User Disabled (PROS)",
+    c"User Autonomous (PROS)",
+    c"User Operator Control (PROS)",
+];
+
+pub(crate) fn register(task: TaskHandle) {
+    REGISTRY.lock_unpoisoned().push(task);
+}
+
+pub(crate) fn deregister(task: &TaskHandle) {
+    REGISTRY.lock_unpoisoned().retain(|other| other != task);
+}
+
+/// Returns the tasks spawned by pros-rs that have started running and have not finished yet.
+///
+/// Tasks created with the raw PROS API are not included. Use [`count`](super::count) to get the
+/// number of tasks the kernel is managing. Tasks must be deleted with [`TaskHandle::abort`] or
+/// [`JoinHandle::abort`](super::JoinHandle::abort) rather than the raw PROS API, or they will not be removed from this list.
+pub fn running_tasks() -> Vec<TaskHandle> {
+    REGISTRY.lock_unpoisoned().clone()
+}
+
+/// A snapshot of information about a task.
+#[derive(Debug, Clone)]
+pub struct TaskInfo {
+    /// The name of the task.
+    pub name: String,
+    /// The state of the task.
+    pub state: TaskState,
+    /// The priority of the task.
+    pub priority: u32,
+    /// The smallest amount of free stack space the task has had since it started, in bytes.
+    pub stack_high_water_mark: usize,
+}
+
+impl TaskHandle {
+    /// Returns a snapshot of information about the task.
+    pub fn info(&self) -> TaskInfo {
+        TaskInfo {
+            name: self.name().unwrap_or_else(|_| "<unknown>".to_string()),
+            state: self.state(),
+            priority: self.priority(),
+            stack_high_water_mark: self.stack_high_water_mark(),
+        }
+    }
+}
+
+/// The CPU usage of a task, as estimated by a [`TaskMonitor`].
+#[derive(Debug, Clone)]
+pub struct TaskUsage {
+    /// Information about the task at the time of the report.
+    pub info: TaskInfo,
+    /// The estimated fraction of CPU time used by the task, from 0 to 1.
+    pub cpu_usage: f32,
+}
+
+/// A report of the CPU usage of every known task, created with [`TaskMonitor::report`].
+#[derive(Debug, Clone)]
+pub struct MonitorReport {
+    /// The number of samples the CPU usage estimates are based on.
+    pub samples: u32,
+    /// The tasks that are currently running, sorted by CPU usage from highest to lowest.
+    pub tasks: Vec<TaskUsage>,
+}
+
+impl fmt::Display for MonitorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<32} {:<10} {:>4} {:>8} {:>6}",
+            "TASK", "STATE", "PRIO", "STACK", "CPU"
+        )?;
+        for task in &self.tasks {
+            writeln!(
+                f,
+                "{:<32} {:<10} {:>4} {:>8} {:>5.1}%",
+                task.info.name,
+                alloc::format!("{:?}", task.info.state),
+                task.info.priority,
+                task.info.stack_high_water_mark,
+                task.cpu_usage * 100.0,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct MonitorStats {
+    samples: u32,
+    /// The number of samples attributed to each task, keyed by task handle.
+    busy: BTreeMap<usize, f32>,
+}
+
+/// Estimates the CPU usage of tasks by periodically sampling their states.
+///
+/// The monitor runs a high priority task that wakes up every sampling interval. At each sample, the
+/// highest priority task that is ready to run is assumed to have been running, since FreeRTOS always
+/// runs the highest priority ready task. Over many samples this approximates the share of CPU time
+/// each task is using. Only tasks spawned by pros-rs and the PROS user tasks are tracked; samples where
+/// none of them were ready are attributed to the idle task and other system tasks.
+///
+/// Stack usage is reported as a high-water mark, which is the closest a task has come to overflowing its stack.
+#[derive(Debug)]
+pub struct TaskMonitor {
+    stats: Arc<Mutex<MonitorStats>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl TaskMonitor {
+    /// Starts a monitor that samples task states every `interval`.
+    pub fn start(interval: Duration) -> Self {
+        let stats = Arc::new(Mutex::new(MonitorStats::default()));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        Builder::new()
+            .name("pros-rs task monitor")
+            .priority(TaskPriority::High)
+            .spawn({
+                let stats = stats.clone();
+                let stopped = stopped.clone();
+                move || {
+                    while !stopped.load(Ordering::Relaxed) {
+                        sample(&stats);
+                        delay(interval);
+                    }
+                }
+            })
+            .expect("Failed to spawn task monitor");
+
+        Self { stats, stopped }
+    }
+
+    /// Returns the CPU and stack usage of every known task since the monitor was started or last reset.
+    pub fn report(&self) -> MonitorReport {
+        let (samples, busy) = {
+            let stats = self.stats.lock_unpoisoned();
+            (stats.samples, stats.busy.clone())
+        };
+        let cpu_usage = |task: &TaskHandle| {
+            let busy = busy.get(&(task.task as usize)).copied().unwrap_or(0.0);
+            if samples == 0 {
+                0.0
+            } else {
+                busy / samples as f32
+            }
+        };
+
+        let mut tasks: Vec<_> = REGISTRY
+            .lock_unpoisoned()
+            .iter()
+            .map(|task| TaskUsage {
+                info: task.info(),
+                cpu_usage: cpu_usage(task),
+            })
+            .collect();
+
+        for name in PROS_TASKS {
+            // PROS may delete these tasks at any time, so the scheduler is suspended
+            // to make sure the handle stays valid while it is being queried.
+            let info = unsafe {
+                let _guard = suspend_all();
+                let task = pros_sys::task_get_by_name(name.as_ptr());
+                (!task.is_null()).then(|| {
+                    let task = TaskHandle { task };
+                    let usage = cpu_usage(&task);
+                    (
+                        task.state(),
+                        task.priority(),
+                        task.stack_high_water_mark(),
+                        usage,
+                    )
+                })
+            };
+
+            if let Some((state, priority, stack_high_water_mark, cpu_usage)) = info {
+                tasks.push(TaskUsage {
+                    info: TaskInfo {
+                        name: name.to_string_lossy().into_owned(),
+                        state,
+                        priority,
+                        stack_high_water_mark,
+                    },
+                    cpu_usage,
+                });
+            }
+        }
+
+        tasks.sort_by(|a, b| b.cpu_usage.total_cmp(&a.cpu_usage));
+
+        MonitorReport { samples, tasks }
+    }
+
+    /// Clears the collected samples, so that the next report only covers the time since this call.
+    pub fn reset(&self) {
+        *self.stats.lock_unpoisoned() = MonitorStats::default();
+    }
+
+    /// Stops the monitor. The sampling task exits after its next sample.
+    pub fn stop(self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Takes a single sample, attributing it to the highest priority ready task.
+fn sample(stats: &Mutex<MonitorStats>) {
+    let registry = REGISTRY.lock_unpoisoned();
+    let current = super::current();
+
+    // Memory must not be allocated while the scheduler is suspended.
+    let mut candidates = Vec::with_capacity(registry.len() + PROS_TASKS.len());
+    let mut running = Vec::with_capacity(candidates.capacity());
+
+    {
+        let _guard = unsafe { suspend_all() };
+
+        candidates.extend(registry.iter().map(|task| task.task));
+        for name in PROS_TASKS {
+            let task = unsafe { pros_sys::task_get_by_name(name.as_ptr()) };
+            if !task.is_null() {
+                candidates.push(task);
+            }
+        }
+
+        let mut highest_priority = 0;
+        for task in candidates.drain(..) {
+            if task == current.task {
+                continue;
+            }
+
+            let task = TaskHandle { task };
+            if !matches!(task.state(), TaskState::Ready | TaskState::Running) {
+                continue;
+            }
+
+            let priority = task.priority();
+            if priority > highest_priority {
+                highest_priority = priority;
+                running.clear();
+            }
+            if priority == highest_priority {
+                running.push(task.task as usize);
+            }
+        }
+    }
+    drop(registry);
+
+    let mut stats = stats.lock_unpoisoned();
+    stats.samples += 1;
+    let share = 1.0 / running.len().max(1) as f32;
+    for task in running {
+        *stats.busy.entry(task).or_default() += share;
+    }
+}
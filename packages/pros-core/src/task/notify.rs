@@ -0,0 +1,110 @@
+//! Typed task notifications.
+//!
+//! Every task has a 32-bit notification value that other tasks can modify when notifying it.
+//! Waiting on a notification blocks the task until the value is nonzero, which makes notifications
+//! a lightweight alternative to semaphores and event flags when there is a single receiving task.
+
+use core::time::Duration;
+
+use super::TaskHandle;
+use crate::sync::timeout_millis;
+
+/// An action to perform on a task's notification value when notifying it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyAction {
+    /// Notify the task without changing its notification value.
+    None,
+    /// Set the given bits in the notification value.
+    /// This is useful for event flags, where each bit represents a different event.
+    SetBits(u32),
+    /// Increment the notification value, like posting to a counting semaphore.
+    Increment,
+    /// Overwrite the notification value with the given value.
+    Overwrite(u32),
+    /// Set the notification value to the given value,
+    /// unless the task already has a pending notification.
+    NoOverwrite(u32),
+}
+
+impl NotifyAction {
+    const fn into_raw(self) -> (u32, pros_sys::notify_action_e_t) {
+        match self {
+            Self::None => (0, pros_sys::E_NOTIFY_ACTION_NONE),
+            Self::SetBits(bits) => (bits, pros_sys::E_NOTIFY_ACTION_BITS),
+            Self::Increment => (0, pros_sys::E_NOTIFY_ACTION_INCR),
+            Self::Overwrite(value) => (value, pros_sys::E_NOTIFY_ACTION_OWRITE),
+            Self::NoOverwrite(value) => (value, pros_sys::E_NOTIFY_ACTION_NO_OWRITE),
+        }
+    }
+}
+
+/// The result of notifying a task with [`TaskHandle::notify_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotifyResult {
+    /// The task's notification value before it was notified.
+    pub previous_value: u32,
+    /// Whether the notification value was written.
+    /// This is only `false` for [`NotifyAction::NoOverwrite`] when the task already had a pending notification.
+    pub written: bool,
+}
+
+impl TaskHandle {
+    /// Sends a notification to the task, performing `action` on its notification value.
+    pub fn notify_with(&self, action: NotifyAction) -> NotifyResult {
+        let (value, action) = action.into_raw();
+        let mut previous_value = 0;
+
+        let not_written = unsafe {
+            pros_sys::task_notify_ext(
+                self.task,
+                value,
+                action,
+                (&mut previous_value as *mut u32).cast_const(),
+            )
+        };
+
+        NotifyResult {
+            previous_value,
+            written: not_written == 0,
+        }
+    }
+
+    /// Clears the task's pending notification, setting its notification value to zero.
+    ///
+    /// Returns `true` if there was a pending notification.
+    pub fn clear_notification(&self) -> bool {
+        unsafe { pros_sys::task_notify_clear(self.task) }
+    }
+
+    /// Notifies `task_to_notify` with `action` when this task is deleted.
+    pub fn notify_when_deleted(&self, task_to_notify: &TaskHandle, action: NotifyAction) {
+        let (value, action) = action.into_raw();
+        unsafe {
+            pros_sys::apix::task_notify_when_deleting(
+                self.task,
+                task_to_notify.task,
+                value,
+                action,
+            );
+        }
+    }
+}
+
+/// Waits for the current task's notification value to be nonzero, then clears it.
+///
+/// Returns the notification value before it was cleared,
+/// or `None` if no notification was received before `timeout` elapsed.
+pub fn wait_notification(timeout: Duration) -> Option<u32> {
+    let value = unsafe { pros_sys::task_notify_take(true, timeout_millis(timeout)) };
+    (value != 0).then_some(value)
+}
+
+/// Waits for the current task's notification value to be nonzero, then decrements it.
+///
+/// Combined with [`NotifyAction::Increment`], this makes the notification value behave like a counting semaphore.
+/// Returns the notification value before it was decremented,
+/// or `None` if no notification was received before `timeout` elapsed.
+pub fn take_notification(timeout: Duration) -> Option<u32> {
+    let value = unsafe { pros_sys::task_notify_take(false, timeout_millis(timeout)) };
+    (value != 0).then_some(value)
+}
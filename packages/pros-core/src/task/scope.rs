@@ -0,0 +1,192 @@
+//! Scoped tasks that can borrow data from the stack.
+
+use alloc::sync::Arc;
+use core::marker::PhantomData;
+
+use super::{
+    join::{Packet, ScopeGuard},
+    spawn_inner, Builder, JoinError, JoinHandle, SpawnError,
+};
+use crate::sync::{Condvar, Mutex, PoisonError};
+
+/// Creates a scope for spawning scoped tasks.
+///
+/// The function passed to `scope` is given a [`Scope`] that can be used to spawn tasks.
+/// Unlike tasks spawned with [`spawn`](super::spawn), scoped tasks can borrow non-`'static` data,
+/// because every task spawned in the scope is joined before this function returns.
+///
+/// # Panics
+///
+/// If any of the tasks spawned in the scope panicked and was not joined manually, this function will panic
+/// once every task has finished.
+///
+/// If the current task panics or is aborted while the scoped tasks are running, it can't be deleted without
+/// freeing the stack they borrow from. A task spawned with a [`JoinHandle`] that panics inside a scope exits
+/// the program, and one that is aborted inside a scope is deleted once the scope ends.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut readings = [0.0; 64];
+/// let (left, right) = readings.split_at_mut(32);
+///
+/// pros::task::scope(|s| {
+///     s.spawn(|| process(left));
+///     s.spawn(|| process(right));
+/// });
+/// ```
+pub fn scope<'env, F, T>(f: F) -> T
+where
+    F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> T,
+{
+    let scope = Scope {
+        data: Arc::new(ScopeData {
+            state: Mutex::new(ScopeState::default()),
+            finished: Condvar::new(),
+        }),
+        scope: PhantomData,
+        env: PhantomData,
+    };
+
+    let guard = ScopeGuard::enter();
+    let output = f(&scope);
+
+    let state = scope.data.state.lock_unpoisoned();
+    let state = scope
+        .data
+        .finished
+        .wait_while(state, |state| state.running > 0)
+        .unwrap_or_else(PoisonError::into_inner);
+    let unhandled_panics = state.unhandled_panics;
+    drop(state);
+
+    // Every scoped task has finished, so the current task no longer needs to be kept alive.
+    drop(guard);
+
+    if unhandled_panics > 0 {
+        panic!("a scoped task panicked");
+    }
+
+    output
+}
+
+/// A scope to spawn scoped tasks in. See [`scope`] for details.
+pub struct Scope<'scope, 'env: 'scope> {
+    data: Arc<ScopeData>,
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+#[derive(Default)]
+struct ScopeState {
+    running: usize,
+    unhandled_panics: usize,
+}
+
+pub(crate) struct ScopeData {
+    state: Mutex<ScopeState>,
+    finished: Condvar,
+}
+
+impl ScopeData {
+    /// Records that a task in the scope panicked. This must be called before the panic is stored in its packet,
+    /// so that joining the task can't observe the panic before it has been recorded.
+    pub(crate) fn task_panicked(&self) {
+        self.state.lock_unpoisoned().unhandled_panics += 1;
+    }
+
+    /// Marks a task in the scope as finished. This must be the last thing the task does with borrowed data.
+    pub(crate) fn task_finished(&self) {
+        let mut state = self.state.lock_unpoisoned();
+        state.running -= 1;
+        if state.running == 0 {
+            self.finished.notify_all();
+        }
+    }
+}
+
+impl<'scope> Scope<'scope, '_> {
+    /// Spawns a new task within the scope, returning a [`ScopedJoinHandle`] for it.
+    ///
+    /// The task will be joined at the end of the scope if it is not joined manually.
+    pub fn spawn<F, T>(&'scope self, f: F) -> ScopedJoinHandle<'scope, T>
+    where
+        F: FnOnce() -> T + Send + 'scope,
+        T: Send + 'scope,
+    {
+        Builder::new()
+            .spawn_scoped(self, f)
+            .expect("Failed to spawn task")
+    }
+}
+
+impl core::fmt::Debug for Scope<'_, '_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Scope").finish_non_exhaustive()
+    }
+}
+
+impl Builder<'_> {
+    /// Builds and spawns a task within a [`Scope`].
+    pub fn spawn_scoped<'scope, F, T>(
+        self,
+        scope: &'scope Scope<'scope, '_>,
+        function: F,
+    ) -> Result<ScopedJoinHandle<'scope, T>, SpawnError>
+    where
+        F: FnOnce() -> T + Send + 'scope,
+        T: Send + 'scope,
+    {
+        let packet = Arc::new(Packet::new());
+        // SAFETY: `scope` waits for every task spawned in it to finish before `'scope` ends.
+        let function = unsafe { packet.wrap_unchecked(function, Some(scope.data.clone())) };
+
+        scope.data.state.lock_unpoisoned().running += 1;
+        let task = spawn_inner(
+            function,
+            self.priority.unwrap_or_default(),
+            self.stack_depth.unwrap_or_default(),
+            self.name,
+        )
+        .inspect_err(|_| scope.data.task_finished())?;
+
+        Ok(ScopedJoinHandle {
+            handle: JoinHandle::new(task, packet),
+            scope: scope.data.clone(),
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// An owned permission to join on a scoped task, getting its return value.
+pub struct ScopedJoinHandle<'scope, T> {
+    handle: JoinHandle<T>,
+    scope: Arc<ScopeData>,
+    _marker: PhantomData<&'scope ()>,
+}
+
+impl<T> ScopedJoinHandle<'_, T> {
+    /// Returns `true` if the task has finished, either by returning or by panicking.
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Waits for the task to finish, and then returns its return value.
+    ///
+    /// Returns an error if the task panicked. Joining a task that panicked prevents the scope from panicking.
+    pub fn join(self) -> Result<T, JoinError> {
+        let result = self.handle.join();
+        if let Err(JoinError::Panicked { .. }) = result {
+            self.scope.state.lock_unpoisoned().unhandled_panics -= 1;
+        }
+        result
+    }
+}
+
+impl<T> core::fmt::Debug for ScopedJoinHandle<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ScopedJoinHandle")
+            .field("handle", &self.handle)
+            .finish()
+    }
+}
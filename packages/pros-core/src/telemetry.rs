@@ -0,0 +1,197 @@
+//! Binary telemetry over the serial connection.
+//!
+//! [`Telemetry`] streams the values of named numeric channels, such as motor velocities, the robot's pose
+//! or the battery voltage, as compact binary frames on stdout. The stream can be captured on a computer
+//! connected to the brain and converted into CSV for plotting with the `pros-telemetry` tool. See the
+//! [`pros_telemetry`] crate for the wire format and how to install it.
+//!
+//! # Serial output
+//!
+//! **While a [`Telemetry`] stream exists, PROS serial stream multiplexing is disabled for the whole program**,
+//! since it would wrap the frames in its own encoding. This changes how everything printed to stdout and
+//! stderr is sent: text is still sent and is ignored by the decoder, but the normal `pros terminal` can no longer
+//! display it. It must be viewed with a raw serial terminal, such as `pros terminal --raw`, and the stream can be
+//! captured with a command like `cat /dev/ttyACM1 > practice.bin`. Multiplexing is enabled again once every
+//! [`Telemetry`] has been dropped.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut telemetry = Telemetry::new(Duration::from_millis(20));
+//! let velocity = telemetry.channel("drive.left_velocity");
+//! let battery = telemetry.channel("battery");
+//!
+//! loop {
+//!     telemetry.set(velocity, left_motor.velocity()?);
+//!     telemetry.set(battery, battery::voltage()?);
+//!     telemetry.publish();
+//!     delay(Duration::from_millis(10));
+//! }
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use pros_telemetry::{Frame, PROTOCOL_VERSION};
+
+use crate::time::Instant;
+
+/// The number of [`Telemetry`] streams that exist, which keep PROS serial stream multiplexing disabled.
+static ACTIVE_STREAMS: AtomicUsize = AtomicUsize::new(0);
+
+/// How often channel registrations are repeated, so that a capture started partway through can be decoded.
+const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A channel registered with [`Telemetry::channel`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Channel(u8);
+
+/// A telemetry stream of named numeric channels.
+///
+/// Values are stored with [`Telemetry::set`] and sent by [`Telemetry::publish`], which sends at most one
+/// sample per interval containing the latest value of every channel that was set since the last sample.
+/// This keeps the serial connection from being saturated no matter how often values are updated.
+#[derive(Debug)]
+pub struct Telemetry {
+    channels: Vec<String>,
+    values: Vec<Option<f32>>,
+    interval: Duration,
+    last_sample: Option<Instant>,
+    last_announcement: Option<Instant>,
+    buffer: Vec<u8>,
+}
+
+impl Telemetry {
+    /// Creates a telemetry stream that sends at most one sample per `interval`.
+    ///
+    /// **This disables PROS serial stream multiplexing for the whole program until the stream is dropped**,
+    /// so printed text can only be viewed with a raw serial terminal. See the [module-level documentation](self).
+    pub fn new(interval: Duration) -> Self {
+        if ACTIVE_STREAMS.fetch_add(1, Ordering::AcqRel) == 0 {
+            unsafe {
+                pros_sys::apix::serctl(pros_sys::apix::SERCTL_DISABLE_COBS, core::ptr::null_mut());
+            }
+        }
+
+        Self {
+            channels: Vec::new(),
+            values: Vec::new(),
+            interval,
+            last_sample: None,
+            last_announcement: None,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Registers a channel named `name`, or returns the existing channel with that name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than 256 channels are registered.
+    pub fn channel(&mut self, name: &str) -> Channel {
+        if let Some(id) = self.channels.iter().position(|channel| channel == name) {
+            return Channel(id as u8);
+        }
+
+        let id = u8::try_from(self.channels.len()).expect("at most 256 channels can be registered");
+        self.channels.push(name.to_string());
+        self.values.push(None);
+        // Announce the new channel with the next sample.
+        self.last_announcement = None;
+
+        Channel(id)
+    }
+
+    /// Sets the value of a channel, to be sent with the next sample.
+    ///
+    /// Values are sent as `f32`s.
+    pub fn set(&mut self, channel: Channel, value: f64) {
+        self.values[channel.0 as usize] = Some(value as f32);
+    }
+
+    /// Sends a sample of every channel that was set since the last sample, if at least one interval has passed.
+    ///
+    /// This should be called regularly, such as once per loop iteration.
+    /// Returns `true` if a sample was sent. Errors writing to the serial connection are ignored.
+    pub fn publish(&mut self) -> bool {
+        let now = Instant::now();
+        if self
+            .last_sample
+            .is_some_and(|last| now.duration_since(last) < self.interval)
+        {
+            return false;
+        }
+
+        if self
+            .last_announcement
+            .map_or(true, |last| now.duration_since(last) >= ANNOUNCE_INTERVAL)
+        {
+            self.announce();
+            self.last_announcement = Some(now);
+        }
+
+        let values: Vec<_> = self
+            .values
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(id, value)| Some((id as u8, value.take()?)))
+            .collect();
+        let sent = !values.is_empty();
+        if sent {
+            Frame::Sample {
+                timestamp: now.since_start().as_millis() as u32,
+                values,
+            }
+            .encode(&mut self.buffer);
+            self.last_sample = Some(now);
+        }
+
+        self.flush();
+        sent
+    }
+
+    /// Queues the protocol version and the name of every channel.
+    fn announce(&mut self) {
+        Frame::Hello {
+            version: PROTOCOL_VERSION,
+        }
+        .encode(&mut self.buffer);
+
+        for (id, name) in self.channels.iter().enumerate() {
+            Frame::Channel {
+                id: id as u8,
+                name: name.clone(),
+            }
+            .encode(&mut self.buffer);
+        }
+    }
+
+    /// Writes the queued frames to stdout.
+    fn flush(&mut self) {
+        let mut written = 0;
+        while written < self.buffer.len() {
+            let remaining = &self.buffer[written..];
+            match unsafe { pros_sys::write(1, remaining.as_ptr().cast(), remaining.len()) } {
+                count if count > 0 => written += count as usize,
+                _ => break,
+            }
+        }
+        self.buffer.clear();
+    }
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if ACTIVE_STREAMS.fetch_sub(1, Ordering::AcqRel) == 1 {
+            unsafe {
+                pros_sys::apix::serctl(pros_sys::apix::SERCTL_ENABLE_COBS, core::ptr::null_mut());
+            }
+        }
+    }
+}
@@ -28,6 +28,11 @@ impl Instant {
         Self(unsafe { pros_sys::rtos::micros() })
     }
 
+    /// Returns the amount of time elapsed between the start of the user program and this instant.
+    pub(crate) const fn since_start(&self) -> Duration {
+        Duration::from_micros(self.0)
+    }
+
     /// Returns the amount of time elapsed from another instant to this one,
     /// or zero duration if that instant is later than this one.
     ///
@@ -0,0 +1,186 @@
+//! Software timers that run callbacks on a shared task.
+//!
+//! Timers are useful for small jobs that need to run periodically or after a delay, such as blinking an LED,
+//! without spawning a dedicated FreeRTOS task for each one. Every timer runs on a single timer task, in the
+//! order of their deadlines. Callbacks should return quickly, since a slow callback delays every other timer.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let blink = timer::every(Duration::from_millis(500), move || led.toggle());
+//! // ...
+//! blink.cancel();
+//! ```
+
+use alloc::{boxed::Box, collections::BTreeMap, sync::Arc};
+use core::{
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::Duration,
+};
+
+use crate::{
+    sync::{LazyLock, Mutex},
+    task::{self, Builder, TaskHandle, TaskPriority},
+    time::Instant,
+};
+
+static SERVICE: LazyLock<TimerService> = LazyLock::new(TimerService::start);
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+enum Callback {
+    Once(Box<dyn FnOnce() + Send>),
+    Periodic {
+        period: Duration,
+        callback: Box<dyn FnMut() + Send>,
+    },
+}
+
+struct Timer {
+    callback: Callback,
+    cancelled: Arc<AtomicBool>,
+}
+
+struct TimerService {
+    timers: Mutex<BTreeMap<(Instant, u64), Timer>>,
+    task: TaskHandle,
+}
+
+impl TimerService {
+    fn start() -> Self {
+        let task = Builder::new()
+            .name("pros-rs timers")
+            .priority(TaskPriority::High)
+            .spawn(|| SERVICE.run())
+            .expect("Failed to spawn timer task");
+
+        Self {
+            timers: Mutex::new(BTreeMap::new()),
+            task,
+        }
+    }
+
+    fn schedule(&self, deadline: Instant, callback: Callback) -> TimerHandle {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let is_next = {
+            let mut timers = self.timers.lock_unpoisoned();
+            timers.insert(
+                (deadline, id),
+                Timer {
+                    callback,
+                    cancelled: cancelled.clone(),
+                },
+            );
+            timers.first_key_value().is_some_and(|(key, _)| key.1 == id)
+        };
+
+        // The timer task may be waiting for a later deadline.
+        if is_next {
+            self.task.notify();
+        }
+
+        TimerHandle { cancelled }
+    }
+
+    fn run(&self) -> ! {
+        loop {
+            let now = Instant::now();
+            let due = {
+                let mut timers = self.timers.lock_unpoisoned();
+                match timers.first_entry() {
+                    Some(entry) if entry.key().0 <= now => Some(entry.remove_entry()),
+                    Some(entry) => {
+                        let until_due = entry.key().0.duration_since(now);
+                        drop(timers);
+                        // Wake up early if a timer with an earlier deadline is scheduled.
+                        task::wait_notification(until_due.max(Duration::from_millis(1)));
+                        None
+                    }
+                    None => {
+                        drop(timers);
+                        task::wait_notification(Duration::MAX);
+                        None
+                    }
+                }
+            };
+
+            let Some(((deadline, id), timer)) = due else {
+                continue;
+            };
+
+            if timer.cancelled.load(Ordering::Relaxed) {
+                continue;
+            }
+
+            match timer.callback {
+                Callback::Once(callback) => {
+                    callback();
+                    timer.cancelled.store(true, Ordering::Relaxed);
+                }
+                Callback::Periodic {
+                    period,
+                    mut callback,
+                } => {
+                    callback();
+
+                    // Missed periods are skipped rather than run back to back.
+                    let now = Instant::now();
+                    let mut next = deadline + period;
+                    while next <= now {
+                        next += period;
+                    }
+
+                    self.timers.lock_unpoisoned().insert(
+                        (next, id),
+                        Timer {
+                            callback: Callback::Periodic { period, callback },
+                            cancelled: timer.cancelled,
+                        },
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// A handle to a timer, which can be used to cancel it.
+///
+/// Dropping the handle does not cancel the timer.
+#[derive(Debug, Clone)]
+pub struct TimerHandle {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl TimerHandle {
+    /// Cancels the timer. If its callback is currently running, it will not be run again.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the timer has been cancelled, or if it was a one-shot timer that has already run.
+    pub fn is_finished(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Runs `callback` once on the timer task after `delay` has elapsed.
+pub fn after(delay: Duration, callback: impl FnOnce() + Send + 'static) -> TimerHandle {
+    SERVICE.schedule(Instant::now() + delay, Callback::Once(Box::new(callback)))
+}
+
+/// Runs `callback` on the timer task every `period`, starting one period from now.
+///
+/// # Panics
+///
+/// Panics if `period` is zero.
+pub fn every(period: Duration, callback: impl FnMut() + Send + 'static) -> TimerHandle {
+    assert!(!period.is_zero(), "timer period must be nonzero");
+    SERVICE.schedule(
+        Instant::now() + period,
+        Callback::Periodic {
+            period,
+            callback: Box::new(callback),
+        },
+    )
+}
@@ -0,0 +1,314 @@
+//! Software watchdog for detecting stalled tasks.
+//!
+//! A [`Watchdog`] must be [petted](Watchdog::pet) at least once per timeout. If it isn't, its stall action
+//! is run on a shared watchdog task. By default the stall is logged to stderr, but the action can be replaced
+//! with anything, such as stopping every motor. Watchdogs can be petted from FreeRTOS tasks and async futures alike.
+//!
+//! Tasks that should be restarted when they stall can be spawned with [`supervise`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! let watchdog = Watchdog::builder("odometry", Duration::from_millis(50))
+//!     .on_stall(|stall| {
+//!         eprintln!("{stall}");
+//!         stop_drivetrain();
+//!     })
+//!     .register();
+//!
+//! loop {
+//!     update_odometry();
+//!     watchdog.pet();
+//!     delay(Duration::from_millis(10));
+//! }
+//! ```
+
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    sync::{Arc, Weak},
+    vec::Vec,
+};
+use core::{
+    fmt,
+    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+    time::Duration,
+};
+
+use crate::{
+    eprintln,
+    sync::{LazyLock, Mutex, Once},
+    task::{self, Builder, TaskHandle, TaskPriority},
+};
+
+/// How often the watchdog task checks for stalled watchdogs.
+const CHECK_INTERVAL: Duration = Duration::from_millis(5);
+
+static WATCHDOGS: LazyLock<Mutex<Vec<Weak<Entry>>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+static WATCHDOG_TASK: Once = Once::new();
+
+type StallAction = Box<dyn Fn(&Stall) + Send + Sync>;
+
+struct Entry {
+    name: String,
+    task: TaskHandle,
+    timeout: Duration,
+    last_pet: AtomicU32,
+    stalled: AtomicBool,
+    retired: AtomicBool,
+    action: StallAction,
+}
+
+/// Information about a stalled [`Watchdog`], passed to its stall action.
+pub struct Stall {
+    entry: Arc<Entry>,
+    task_name: String,
+    overdue: Duration,
+}
+
+impl Stall {
+    /// The name of the watchdog.
+    pub fn name(&self) -> &str {
+        &self.entry.name
+    }
+
+    /// The task that registered the watchdog.
+    pub fn task(&self) -> &TaskHandle {
+        &self.entry.task
+    }
+
+    /// The name of the task that registered the watchdog, as of when the stall was detected.
+    pub fn task_name(&self) -> &str {
+        &self.task_name
+    }
+
+    /// How long it has been since the watchdog was last petted, minus its timeout.
+    pub const fn overdue(&self) -> Duration {
+        self.overdue
+    }
+
+    /// Stops checking the watchdog, even if its [`Watchdog`] handle is never dropped.
+    ///
+    /// This is useful when the stall action deletes the task that owns the handle.
+    pub fn unregister(&self) {
+        self.entry.retired.store(true, Ordering::Relaxed);
+        WATCHDOGS
+            .lock_unpoisoned()
+            .retain(|other| !core::ptr::eq(other.as_ptr(), Arc::as_ptr(&self.entry)));
+    }
+}
+
+impl fmt::Display for Stall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "watchdog '{}' in task '{}' was not petted for {:?} (timeout is {:?})",
+            self.entry.name,
+            self.task_name,
+            self.entry.timeout + self.overdue,
+            self.entry.timeout,
+        )
+    }
+}
+
+impl fmt::Debug for Stall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stall")
+            .field("name", &self.entry.name)
+            .field("task", &self.entry.task)
+            .field("task_name", &self.task_name)
+            .field("overdue", &self.overdue)
+            .finish()
+    }
+}
+
+/// A builder for a [`Watchdog`], created with [`Watchdog::builder`].
+#[must_use = "the watchdog is not checked until `register` is called"]
+pub struct WatchdogBuilder {
+    name: String,
+    timeout: Duration,
+    action: StallAction,
+}
+
+impl WatchdogBuilder {
+    /// Sets the action to run when the watchdog stalls, replacing the default action of logging the stall.
+    ///
+    /// The action runs on the watchdog task, so it should not block for long.
+    /// It runs once per stall; petting the watchdog again re-arms it.
+    pub fn on_stall(mut self, action: impl Fn(&Stall) + Send + Sync + 'static) -> Self {
+        self.action = Box::new(action);
+        self
+    }
+
+    /// Registers the watchdog, starting its timeout from now.
+    pub fn register(self) -> Watchdog {
+        let entry = Arc::new(Entry {
+            name: self.name,
+            task: task::current(),
+            timeout: self.timeout,
+            last_pet: AtomicU32::new(unsafe { pros_sys::millis() }),
+            stalled: AtomicBool::new(false),
+            retired: AtomicBool::new(false),
+            action: self.action,
+        });
+
+        WATCHDOGS.lock_unpoisoned().push(Arc::downgrade(&entry));
+        WATCHDOG_TASK.call_once(|| {
+            Builder::new()
+                .name("pros-rs watchdog")
+                .priority(TaskPriority::High)
+                .spawn(|| loop {
+                    check();
+                    task::delay(CHECK_INTERVAL);
+                })
+                .expect("Failed to spawn watchdog task");
+        });
+
+        Watchdog { entry }
+    }
+}
+
+impl fmt::Debug for WatchdogBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WatchdogBuilder")
+            .field("name", &self.name)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A watchdog that must be petted at least once per timeout.
+///
+/// The watchdog is unregistered when this handle is dropped.
+pub struct Watchdog {
+    entry: Arc<Entry>,
+}
+
+impl Watchdog {
+    /// Creates a builder for a watchdog with the given name and timeout.
+    pub fn builder(name: impl Into<String>, timeout: Duration) -> WatchdogBuilder {
+        WatchdogBuilder {
+            name: name.into(),
+            timeout,
+            action: Box::new(|stall| eprintln!("{stall}")),
+        }
+    }
+
+    /// Registers a watchdog with the given name and timeout that logs to stderr when it stalls.
+    pub fn new(name: impl Into<String>, timeout: Duration) -> Self {
+        Self::builder(name, timeout).register()
+    }
+
+    /// Resets the watchdog's timeout.
+    pub fn pet(&self) {
+        self.entry
+            .last_pet
+            .store(unsafe { pros_sys::millis() }, Ordering::Relaxed);
+        self.entry.stalled.store(false, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the watchdog has stalled and has not been petted since.
+    pub fn is_stalled(&self) -> bool {
+        self.entry.stalled.load(Ordering::Relaxed)
+    }
+
+    /// The name of the watchdog.
+    pub fn name(&self) -> &str {
+        &self.entry.name
+    }
+}
+
+impl fmt::Debug for Watchdog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Watchdog")
+            .field("name", &self.entry.name)
+            .field("timeout", &self.entry.timeout)
+            .field("stalled", &self.is_stalled())
+            .finish()
+    }
+}
+
+/// Runs the stall action of every watchdog that has not been petted in time.
+fn check() {
+    let now = unsafe { pros_sys::millis() };
+
+    let stalls: Vec<_> = {
+        let mut watchdogs = WATCHDOGS.lock_unpoisoned();
+        watchdogs.retain(|entry| {
+            entry
+                .upgrade()
+                .is_some_and(|entry| !entry.retired.load(Ordering::Relaxed))
+        });
+
+        watchdogs
+            .iter()
+            .filter_map(Weak::upgrade)
+            .filter_map(|entry| {
+                let elapsed = Duration::from_millis(
+                    now.wrapping_sub(entry.last_pet.load(Ordering::Relaxed)) as u64,
+                );
+                let overdue = elapsed.checked_sub(entry.timeout)?;
+                if overdue.is_zero() || entry.stalled.swap(true, Ordering::Relaxed) {
+                    return None;
+                }
+
+                // The name is read now, since the stall action may delete the task.
+                let task_name = entry
+                    .task
+                    .name()
+                    .unwrap_or_else(|_| "<unknown>".to_string());
+                Some(Stall {
+                    entry,
+                    task_name,
+                    overdue,
+                })
+            })
+            .collect()
+    };
+
+    for stall in stalls {
+        (stall.entry.action)(&stall);
+    }
+}
+
+/// Spawns a task that is restarted whenever it stalls.
+///
+/// `f` is run on a new task with a watchdog that it must pet at least once per `timeout`.
+/// If the watchdog stalls, the task is aborted and `f` is run again on a new task.
+///
+/// Aborting a task does not free its memory or release any locks it was holding, so this should only
+/// be used as a last resort for tasks that are not expected to stall.
+pub fn supervise<F>(name: impl Into<String>, timeout: Duration, f: F)
+where
+    F: Fn(&Watchdog) + Send + Sync + 'static,
+{
+    spawn_supervised(Arc::from(name.into()), timeout, Arc::new(f));
+}
+
+fn spawn_supervised<F>(name: Arc<str>, timeout: Duration, f: Arc<F>)
+where
+    F: Fn(&Watchdog) + Send + Sync + 'static,
+{
+    let task_name = name.clone();
+    Builder::new()
+        .name(&task_name)
+        .spawn(move || {
+            let watchdog = Watchdog::builder(&*name, timeout)
+                .on_stall({
+                    let name = name.clone();
+                    let f = f.clone();
+                    move |stall| {
+                        eprintln!("{stall}, restarting it");
+                        // The old task never drops its watchdog, so it is unregistered here,
+                        // and aborting the task removes it from the task registry.
+                        stall.unregister();
+                        stall.task().clone().abort();
+                        spawn_supervised(name.clone(), timeout, f.clone());
+                    }
+                })
+                .register();
+
+            f(&watchdog);
+        })
+        .expect("Failed to spawn supervised task");
+}
@@ -0,0 +1,280 @@
+//! Persistent key-value configuration stored on the SD card.
+//!
+//! A [`Config`] is a set of string keys and values loaded from an INI-like file, which makes it easy to
+//! keep things like tuned PID constants, the selected autonomous and port mappings between power cycles
+//! without recompiling. Values are parsed on access with [`FromStr`], so any type that can be parsed from
+//! a string can be stored.
+//!
+//! # File format
+//!
+//! Each line is either a `key = value` pair, a `[section]` header, a comment starting with `#` or `;`,
+//! or blank. Keys in a section are prefixed with the section name, so `kp` in the `[drive]` section
+//! is read as `drive.kp`.
+//!
+//! ```ini
+//! autonomous = skills
+//!
+//! [drive]
+//! kp = 0.8
+//! kd = 0.05
+//! ```
+//!
+//! # Atomic saves
+//!
+//! The usual way to replace a file atomically is to write a temporary file and rename it over the original.
+//! That isn't possible on the V5 brain: the VEX SDK that PROS uses for the SD card has no way to rename or
+//! delete a file, and PROS doesn't provide `rename` at all (see [`usd::remove`]).
+//!
+//! Instead, saves write the whole file twice: first to `<path>.tmp`, then to `<path>`. A write that is cut off
+//! by a power loss can't be detected from the file alone, so both copies start with a CRC-32 checksum of their
+//! contents. When loading, a copy whose checksum doesn't match is skipped. If the main file was being written,
+//! the temporary copy is complete and is loaded instead, and if the temporary copy was being written, the main
+//! file still holds the previous save. Files without a checksum line are always accepted, so a config file
+//! can be written by hand. If a saved file is edited by hand, its checksum line should be removed.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut config = Config::load("robot.ini")?;
+//! let kp = config.get_or("drive.kp", 0.5);
+//!
+//! config.set("drive.kp", kp + 0.1);
+//! config.save()?;
+//! ```
+
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+};
+use core::{fmt::Display, str::FromStr};
+
+use snafu::Snafu;
+
+use crate::usd::{self, FsError};
+
+/// The prefix of the checksum line at the start of saved config files.
+const CHECKSUM_PREFIX: &str = "# checksum: ";
+
+/// A set of configuration values that can be loaded from and saved to a file on the SD card.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    path: String,
+    values: BTreeMap<String, String>,
+}
+
+impl Config {
+    /// Creates an empty config that will be saved to `path`.
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            values: BTreeMap::new(),
+        }
+    }
+
+    /// Loads a config from `path`.
+    ///
+    /// If the file doesn't exist, an empty config is returned, so that every value falls back to its default.
+    pub fn load(path: &str) -> Result<Self, ConfigError> {
+        let mut config = Self::new(path);
+
+        for candidate in [path.to_string(), temp_path(path)] {
+            match usd::read_to_string(&candidate) {
+                Ok(contents) => {
+                    if let Some(values) = parse(&contents)? {
+                        config.values = values;
+                        return Ok(config);
+                    }
+                }
+                Err(FsError::NotFound) => {}
+                Err(source) => return Err(ConfigError::Fs { source }),
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// The path the config is saved to.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the raw string value of `key`.
+    ///
+    /// Whitespace around keys is ignored, as it is in the file.
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.values.get(key.trim()).map(String::as_str)
+    }
+
+    /// Parses the value of `key`.
+    ///
+    /// Returns `Ok(None)` if the key is not set, or an error if its value could not be parsed.
+    pub fn get<T: FromStr>(&self, key: &str) -> Result<Option<T>, ConfigError> {
+        self.get_str(key)
+            .map(|value| {
+                value.parse().map_err(|_| ConfigError::InvalidValue {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                })
+            })
+            .transpose()
+    }
+
+    /// Parses the value of `key`, or returns `default` if it is not set or could not be parsed.
+    pub fn get_or<T: FromStr>(&self, key: &str, default: T) -> T {
+        self.get(key).ok().flatten().unwrap_or(default)
+    }
+
+    /// Sets the value of `key`, replacing its previous value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is empty, starts with `#` or `;`, or contains `=`, `[` or a newline,
+    /// or if the value contains a newline, since they could not be read back after saving.
+    pub fn set(&mut self, key: &str, value: impl Display) {
+        let value = value.to_string();
+        let key = key.trim();
+        assert!(
+            !key.is_empty() && !key.starts_with(['#', ';']) && !key.contains(['=', '[', '\n']),
+            "invalid config key: {key:?}"
+        );
+        assert!(
+            !value.contains('\n'),
+            "config values cannot contain newlines"
+        );
+
+        self.values.insert(key.to_string(), value);
+    }
+
+    /// Removes `key`, returning its previous value.
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        self.values.remove(key.trim())
+    }
+
+    /// Returns `true` if `key` is set.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.values.contains_key(key.trim())
+    }
+
+    /// Returns an iterator over every key and value, sorted by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Saves the config to its file.
+    ///
+    /// See the [module-level documentation](self#atomic-saves) for how the file is kept intact
+    /// if the robot loses power while saving.
+    pub fn save(&self) -> Result<(), ConfigError> {
+        let mut body = String::new();
+        for (key, value) in &self.values {
+            body.push_str(&format!("{key} = {value}\n"));
+        }
+        let contents = format!("{CHECKSUM_PREFIX}{:08x}\n{body}", checksum(&body));
+
+        // Files can't be renamed on the V5 brain, so both copies are written in full.
+        // At any point, at least one of them is complete and has a valid checksum.
+        usd::write(&temp_path(&self.path), &contents)?;
+        usd::write(&self.path, &contents)?;
+
+        Ok(())
+    }
+}
+
+fn temp_path(path: &str) -> String {
+    format!("{path}.tmp")
+}
+
+/// Parses the contents of a config file.
+///
+/// Returns `Ok(None)` if the file has a checksum that doesn't match its contents,
+/// or if it was cut off before the end of its checksum line.
+fn parse(contents: &str) -> Result<Option<BTreeMap<String, String>>, ConfigError> {
+    if CHECKSUM_PREFIX.starts_with(contents) {
+        return Ok(None);
+    }
+
+    let body = match contents.strip_prefix(CHECKSUM_PREFIX) {
+        Some(rest) => {
+            let (expected, body) = rest.split_once('\n').unwrap_or((rest, ""));
+            if u32::from_str_radix(expected.trim(), 16) != Ok(checksum(body)) {
+                return Ok(None);
+            }
+            body
+        }
+        None => contents,
+    };
+
+    let mut values = BTreeMap::new();
+    let mut section = String::new();
+
+    for (index, line) in body.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(['#', ';']) {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            section = name.trim().to_string();
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(ConfigError::Syntax { line: index + 1 });
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(ConfigError::Syntax { line: index + 1 });
+        }
+
+        let key = if section.is_empty() {
+            key.to_string()
+        } else {
+            format!("{section}.{key}")
+        };
+        values.insert(key, value.trim().to_string());
+    }
+
+    Ok(Some(values))
+}
+
+/// Computes the CRC-32 checksum of `data`.
+fn checksum(data: &str) -> u32 {
+    let mut crc = !0u32;
+    for byte in data.bytes() {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[derive(Debug, Snafu)]
+/// Errors that can occur when loading or saving a [`Config`].
+pub enum ConfigError {
+    /// Line {line} of the config file is not a `key = value` pair, section header or comment.
+    Syntax {
+        /// The line number, starting from 1.
+        line: usize,
+    },
+    /// The value {value:?} of {key:?} could not be parsed.
+    InvalidValue {
+        /// The key of the value.
+        key: String,
+        /// The value that could not be parsed.
+        value: String,
+    },
+    #[snafu(display("{source}"), context(false))]
+    /// The config file could not be read or written.
+    Fs {
+        /// The source of the error.
+        source: FsError,
+    },
+}
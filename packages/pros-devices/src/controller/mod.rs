@@ -2,13 +2,30 @@
 //!
 //! Controllers are identified by their id, which is either 0 (master) or 1 (partner).
 //! State of a controller can be checked by calling [`Controller::state`] which will return a struct with all of the buttons' and joysticks' state.
+//!
+//! Driver inputs can be recorded and replayed later with the [`replay`] module. Code that reads the
+//! controller through the [`ControllerInput`] trait works with both real and replayed controllers.
 
-use alloc::{ffi::CString, vec::Vec};
+use alloc::{ffi::CString, string::String, vec::Vec};
 
-use pros_core::{bail_on, map_errno};
+use pros_core::{
+    bail_on,
+    logger::{LogEntry, Sink},
+    map_errno,
+};
 use pros_sys::{controller_id_e_t, PROS_ERR};
 use snafu::Snafu;
 
+pub mod replay;
+
+/// A source of controller input, such as a real [`Controller`] or a [`Replay`](replay::Replay).
+///
+/// Writing driver control code against this trait allows the same code to run from recorded inputs.
+pub trait ControllerInput {
+    /// Gets the current state of the controller in its entirety.
+    fn state(&self) -> Result<ControllerState, ControllerError>;
+}
+
 /// Holds whether or not the buttons on the controller are pressed or not
 #[derive(Default, Debug, Clone, Copy, Eq, PartialEq)]
 pub struct Buttons {
@@ -51,7 +68,7 @@ pub struct Joystick {
 }
 
 /// Stores both joysticks on the controller.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
 pub struct Joysticks {
     /// Left joystick
     pub left: Joystick,
@@ -60,7 +77,9 @@ pub struct Joysticks {
 }
 
 /// Stores the current state of the controller; the joysticks and buttons.
-#[derive(Debug, Clone, Copy, PartialEq)]
+///
+/// The default state has both joysticks centered and no buttons pressed.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
 pub struct ControllerState {
     /// Analog joysticks state
     pub joysticks: Joysticks,
@@ -87,7 +106,7 @@ impl ControllerLine {
         let text = text.into();
         let text_len = text.len();
         assert!(
-            text_len > ControllerLine::MAX_TEXT_LEN,
+            text_len <= ControllerLine::MAX_TEXT_LEN,
             "Printed text is too long to fit on controller display ({text_len} > {})",
             Self::MAX_TEXT_LEN
         );
@@ -107,6 +126,41 @@ impl ControllerLine {
     }
 }
 
+/// A [`Sink`] that shows the most recent log entry on a line of the controller display.
+///
+/// Each entry is shown as the first letter of its level followed by as much of the message as fits on the line.
+/// The controller display can only be updated every 50 milliseconds, so entries logged in quick succession
+/// may be dropped. This sink works best when it only receives warnings and errors.
+#[derive(Debug, Clone, Copy)]
+pub struct ControllerSink {
+    line: ControllerLine,
+}
+
+impl ControllerSink {
+    /// Creates a sink that prints to the given controller line.
+    pub const fn new(line: ControllerLine) -> Self {
+        Self { line }
+    }
+}
+
+impl Sink for ControllerSink {
+    fn write(&self, entry: &LogEntry<'_>) {
+        let message = alloc::format!("{} {}", &entry.level.as_str()[..1], entry.args);
+
+        // Pad the text so that it overwrites any previous entry.
+        let mut text: String = message
+            .chars()
+            .map(|c| if c.is_ascii() && c != '\0' { c } else { '?' })
+            .take(ControllerLine::MAX_TEXT_LEN)
+            .collect();
+        while text.len() < ControllerLine::MAX_TEXT_LEN {
+            text.push(' ');
+        }
+
+        _ = self.line.try_print(text);
+    }
+}
+
 /// A digital channel (button) on the VEX controller.
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -171,7 +225,7 @@ impl Controller {
     /// Returns a line on the controller display that can be used to print to the controller.
     pub fn line(&self, line_num: u8) -> ControllerLine {
         assert!(
-            line_num > ControllerLine::MAX_LINE_NUM,
+            line_num <= ControllerLine::MAX_LINE_NUM,
             "Line number is too large for controller display ({line_num} > {})",
             ControllerLine::MAX_LINE_NUM
         );
@@ -332,6 +386,12 @@ impl Controller {
     }
 }
 
+impl ControllerInput for Controller {
+    fn state(&self) -> Result<ControllerState, ControllerError> {
+        Controller::state(self)
+    }
+}
+
 #[derive(Debug, Snafu)]
 /// Errors that can occur when interacting with the controller.
 pub enum ControllerError {
@@ -0,0 +1,427 @@
+//! Recording and replaying driver inputs.
+//!
+//! A [`ControllerRecorder`] samples a controller at a fixed rate, usually during driver control, and produces
+//! a [`Recording`] that can be saved to the SD card. A [`Replay`] plays a recording back as a virtual
+//! controller that implements [`ControllerInput`], so the same driver control code can run an autonomous
+//! routine, such as a skills run, from the recorded inputs.
+//!
+//! Every snapshot is stored with the time it was taken, and a replay looks up the snapshot for the time that
+//! has passed since it started rather than counting calls. This keeps the replay in sync with the recording
+//! even if the driver control loop runs at a different or uneven rate.
+//!
+//! # File format
+//!
+//! Recordings are stored in a little-endian binary format:
+//!
+//! | Bytes | Contents                                                |
+//! |-------|---------------------------------------------------------|
+//! | 4     | The magic bytes `PRSR`                                  |
+//! | 1     | The format version, currently [`FORMAT_VERSION`]        |
+//! | 1     | Reserved, always 0                                      |
+//! | 2     | The recording interval in milliseconds                  |
+//! | 4     | The number of snapshots                                 |
+//!
+//! followed by 10 bytes per snapshot: the time since the recording started in milliseconds (4 bytes),
+//! the left x, left y, right x and right y joystick axes from -127 to 127 (1 byte each), and a bitmask of
+//! the pressed buttons (2 bytes) in the order A, B, X, Y, up, down, left, right, L1, L2, R1, R2,
+//! starting from the least significant bit.
+//!
+//! # Example
+//!
+//! ```ignore
+//! // During driver control:
+//! let recorder = ControllerRecorder::start(Controller::Master, Duration::from_millis(10));
+//! // ...
+//! recorder.stop().save("skills.rec")?;
+//!
+//! // During autonomous:
+//! let replay = Replay::start(Recording::load("skills.rec")?);
+//! while !replay.is_finished() {
+//!     drive(&replay)?;
+//!     delay(Duration::from_millis(10));
+//! }
+//! ```
+
+use alloc::{sync::Arc, vec::Vec};
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use pros_core::{
+    sync::{Mutex, PoisonError},
+    task::{self, Interval, TaskHandle},
+    time::Instant,
+};
+use snafu::Snafu;
+
+use super::{
+    Buttons, Controller, ControllerError, ControllerInput, ControllerState, Joystick, Joysticks,
+};
+use crate::usd::{self, FsError};
+
+/// The version of the recording file format written by [`Recording::save`].
+pub const FORMAT_VERSION: u8 = 1;
+
+const MAGIC: &[u8; 4] = b"PRSR";
+const HEADER_LEN: usize = 12;
+const SNAPSHOT_LEN: usize = 10;
+
+/// A controller state and the time it was recorded at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Snapshot {
+    /// The time since the recording started.
+    pub time: Duration,
+    /// The state of the controller.
+    pub state: ControllerState,
+}
+
+/// A sequence of recorded controller states.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+    interval: Duration,
+    snapshots: Vec<Snapshot>,
+}
+
+impl Recording {
+    /// Creates a recording from snapshots taken every `interval`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the snapshots are not sorted by time.
+    pub fn new(interval: Duration, snapshots: Vec<Snapshot>) -> Self {
+        assert!(
+            snapshots
+                .windows(2)
+                .all(|pair| pair[0].time <= pair[1].time),
+            "snapshots must be sorted by time"
+        );
+        Self {
+            interval,
+            snapshots,
+        }
+    }
+
+    /// Loads a recording from a file on the SD card.
+    pub fn load(path: &str) -> Result<Self, ReplayError> {
+        Self::from_bytes(&usd::read(path)?)
+    }
+
+    /// Saves the recording to a file on the SD card, replacing it if it exists.
+    pub fn save(&self, path: &str) -> Result<(), ReplayError> {
+        usd::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    /// Decodes a recording from the [file format](self#file-format).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let (header, body) = bytes
+            .split_first_chunk::<HEADER_LEN>()
+            .ok_or(ReplayError::InvalidFormat)?;
+        if &header[..4] != MAGIC {
+            return Err(ReplayError::InvalidFormat);
+        }
+        if header[4] != FORMAT_VERSION {
+            return Err(ReplayError::UnsupportedVersion { version: header[4] });
+        }
+
+        let interval = u16::from_le_bytes([header[6], header[7]]);
+        let count = u32::from_le_bytes([header[8], header[9], header[10], header[11]]) as usize;
+        // The count comes from the file, so a corrupted count must not overflow.
+        if count.checked_mul(SNAPSHOT_LEN) != Some(body.len()) {
+            return Err(ReplayError::InvalidFormat);
+        }
+
+        let snapshots: Vec<_> = body
+            .chunks_exact(SNAPSHOT_LEN)
+            .map(decode_snapshot)
+            .collect();
+        if !snapshots
+            .windows(2)
+            .all(|pair| pair[0].time <= pair[1].time)
+        {
+            return Err(ReplayError::InvalidFormat);
+        }
+
+        Ok(Self {
+            interval: Duration::from_millis(interval.into()),
+            snapshots,
+        })
+    }
+
+    /// Encodes the recording in the [file format](self#file-format).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.snapshots.len() * SNAPSHOT_LEN);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&[FORMAT_VERSION, 0]);
+        bytes.extend_from_slice(
+            &u16::try_from(self.interval.as_millis())
+                .unwrap_or(u16::MAX)
+                .to_le_bytes(),
+        );
+        bytes.extend_from_slice(&(self.snapshots.len() as u32).to_le_bytes());
+        for snapshot in &self.snapshots {
+            encode_snapshot(snapshot, &mut bytes);
+        }
+        bytes
+    }
+
+    /// The interval the recording was sampled at.
+    pub const fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// The recorded snapshots, sorted by time.
+    pub fn snapshots(&self) -> &[Snapshot] {
+        &self.snapshots
+    }
+
+    /// The time of the last snapshot.
+    pub fn duration(&self) -> Duration {
+        self.snapshots
+            .last()
+            .map_or(Duration::ZERO, |snapshot| snapshot.time)
+    }
+
+    /// Returns the state that was recorded at `time`, or `None` if `time` is after the end of the recording.
+    ///
+    /// Before the first snapshot, the default state is returned.
+    pub fn state_at(&self, time: Duration) -> Option<ControllerState> {
+        if time > self.duration() + self.interval {
+            return None;
+        }
+
+        let index = self
+            .snapshots
+            .partition_point(|snapshot| snapshot.time <= time);
+        Some(
+            index
+                .checked_sub(1)
+                .map_or_else(ControllerState::default, |index| {
+                    self.snapshots[index].state
+                }),
+        )
+    }
+}
+
+const BUTTON_COUNT: usize = 12;
+
+const fn buttons_to_array(buttons: &Buttons) -> [bool; BUTTON_COUNT] {
+    [
+        buttons.a,
+        buttons.b,
+        buttons.x,
+        buttons.y,
+        buttons.up,
+        buttons.down,
+        buttons.left,
+        buttons.right,
+        buttons.left_trigger_1,
+        buttons.left_trigger_2,
+        buttons.right_trigger_1,
+        buttons.right_trigger_2,
+    ]
+}
+
+fn encode_axis(value: f32) -> u8 {
+    let scaled = (value * 127.0).clamp(-127.0, 127.0);
+    // Round to the nearest integer, since casting truncates.
+    let rounded = if scaled < 0.0 {
+        scaled - 0.5
+    } else {
+        scaled + 0.5
+    };
+    rounded as i8 as u8
+}
+
+fn decode_axis(value: u8) -> f32 {
+    value as i8 as f32 / 127.0
+}
+
+fn encode_snapshot(snapshot: &Snapshot, bytes: &mut Vec<u8>) {
+    let joysticks = &snapshot.state.joysticks;
+    let buttons = buttons_to_array(&snapshot.state.buttons)
+        .iter()
+        .enumerate()
+        .fold(0u16, |mask, (bit, pressed)| {
+            mask | (u16::from(*pressed) << bit)
+        });
+
+    bytes.extend_from_slice(&(snapshot.time.as_millis() as u32).to_le_bytes());
+    bytes.extend_from_slice(&[
+        encode_axis(joysticks.left.x),
+        encode_axis(joysticks.left.y),
+        encode_axis(joysticks.right.x),
+        encode_axis(joysticks.right.y),
+    ]);
+    bytes.extend_from_slice(&buttons.to_le_bytes());
+}
+
+fn decode_snapshot(bytes: &[u8]) -> Snapshot {
+    let time = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    let mask = u16::from_le_bytes([bytes[8], bytes[9]]);
+    let pressed = |bit: u32| mask & (1 << bit) != 0;
+
+    Snapshot {
+        time: Duration::from_millis(time.into()),
+        state: ControllerState {
+            joysticks: Joysticks {
+                left: Joystick {
+                    x: decode_axis(bytes[4]),
+                    y: decode_axis(bytes[5]),
+                },
+                right: Joystick {
+                    x: decode_axis(bytes[6]),
+                    y: decode_axis(bytes[7]),
+                },
+            },
+            buttons: Buttons {
+                a: pressed(0),
+                b: pressed(1),
+                x: pressed(2),
+                y: pressed(3),
+                up: pressed(4),
+                down: pressed(5),
+                left: pressed(6),
+                right: pressed(7),
+                left_trigger_1: pressed(8),
+                left_trigger_2: pressed(9),
+                right_trigger_1: pressed(10),
+                right_trigger_2: pressed(11),
+            },
+        },
+    }
+}
+
+/// Records the state of a controller at a fixed rate on its own task.
+///
+/// Recording stops when [`ControllerRecorder::stop`] is called or the recorder is dropped.
+#[derive(Debug)]
+pub struct ControllerRecorder {
+    interval: Duration,
+    running: Arc<AtomicBool>,
+    snapshots: Arc<Mutex<Vec<Snapshot>>>,
+    task: Option<TaskHandle>,
+}
+
+impl ControllerRecorder {
+    /// Starts recording `controller` every `interval`.
+    ///
+    /// If the controller can't be read, such as when it is disconnected, the default state is recorded.
+    pub fn start(controller: Controller, interval: Duration) -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        let snapshots = Arc::new(Mutex::new(Vec::new()));
+
+        let task = task::spawn({
+            let running = running.clone();
+            let snapshots = snapshots.clone();
+            move || {
+                let start = Instant::now();
+                let mut timer = Interval::start();
+                while running.load(Ordering::Relaxed) {
+                    let snapshot = Snapshot {
+                        time: start.elapsed(),
+                        state: controller.state().unwrap_or_default(),
+                    };
+                    snapshots
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .push(snapshot);
+
+                    timer.delay(interval);
+                }
+            }
+        });
+
+        Self {
+            interval,
+            running,
+            snapshots,
+            task: Some(task),
+        }
+    }
+
+    /// Stops recording and returns the recorded snapshots.
+    pub fn stop(mut self) -> Recording {
+        self.stop_task();
+        let snapshots = core::mem::take(
+            &mut *self
+                .snapshots
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+        Recording::new(self.interval, snapshots)
+    }
+
+    fn stop_task(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(task) = self.task.take() {
+            task.join();
+        }
+    }
+}
+
+impl Drop for ControllerRecorder {
+    fn drop(&mut self) {
+        self.stop_task();
+    }
+}
+
+/// A virtual controller that plays back a [`Recording`].
+///
+/// Once the recording has finished, the controller reports the default state, with the joysticks
+/// centered and no buttons pressed, so that driver control code stops the robot.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    recording: Recording,
+    start: Instant,
+}
+
+impl Replay {
+    /// Starts playing `recording` from the beginning.
+    pub fn start(recording: Recording) -> Self {
+        Self {
+            recording,
+            start: Instant::now(),
+        }
+    }
+
+    /// The time since the replay started.
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Returns `true` if the whole recording has been played.
+    pub fn is_finished(&self) -> bool {
+        self.recording.state_at(self.elapsed()).is_none()
+    }
+
+    /// The recording being played.
+    pub const fn recording(&self) -> &Recording {
+        &self.recording
+    }
+}
+
+impl ControllerInput for Replay {
+    fn state(&self) -> Result<ControllerState, ControllerError> {
+        Ok(self.recording.state_at(self.elapsed()).unwrap_or_default())
+    }
+}
+
+#[derive(Debug, Snafu)]
+/// Errors that can occur when loading or saving a [`Recording`].
+pub enum ReplayError {
+    /// The file is not a valid controller recording.
+    InvalidFormat,
+    /// The recording was saved with an unsupported format version: {version}.
+    UnsupportedVersion {
+        /// The format version of the recording.
+        version: u8,
+    },
+    #[snafu(display("{source}"), context(false))]
+    /// The recording could not be read or written.
+    Fs {
+        /// The source of the error.
+        source: FsError,
+    },
+}
@@ -13,6 +13,8 @@
 //! - [`battery`] provides functions for getting information about the currently connected
 //!   battery.
 //! - [`controller`] provides types for interacting with the V5 controller.
+//! - [`usd`] provides access to files on the SD card, and [`config`] stores settings in them.
+//! - [`recorder`] samples sensor data and saves it to the SD card as CSV.
 
 #![no_std]
 
@@ -23,13 +25,15 @@ pub mod smart;
 
 pub mod battery;
 pub mod color;
-pub mod competition;
+pub mod config;
 pub mod controller;
 pub mod peripherals;
 pub mod position;
+pub mod recorder;
 pub mod screen;
 pub mod usd;
 
 pub use controller::Controller;
 pub use position::Position;
+pub use pros_core::competition;
 pub use screen::Screen;
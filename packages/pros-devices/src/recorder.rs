@@ -0,0 +1,328 @@
+//! Sensor data recording to the SD card.
+//!
+//! A [`Recorder`] samples a set of columns at a fixed rate on its own task and stores the samples in a
+//! preallocated ring buffer. The buffer is written to a CSV file on the SD card at the end of every
+//! competition period and whenever [`Recorder::flush`] is called, so data from a whole match can be
+//! analyzed afterwards without streaming it over the serial connection during the match.
+//!
+//! The CSV file has a `time` column with the time since the program started in seconds, followed by
+//! one column per recorded value. A cell is left empty if its value could not be read.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let recorder = Recorder::builder("match.csv")
+//!     .interval(Duration::from_millis(10))
+//!     .capacity(2000)
+//!     .column("left_velocity", move || left_motor.velocity().ok())
+//!     .column("heading", move || imu.heading().ok())
+//!     .start();
+//! ```
+
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    sync::Arc,
+    vec,
+    vec::Vec,
+};
+use core::{
+    fmt::{self, Write as _},
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use pros_core::{
+    sync::{Mutex, MutexGuard, PoisonError},
+    task::{self, Interval, TaskHandle},
+};
+
+use crate::{
+    competition,
+    usd::{self, FsError},
+};
+
+type Source = Box<dyn FnMut() -> Option<f64> + Send>;
+
+/// Samples values at a fixed rate and saves them to a CSV file on the SD card.
+///
+/// Created with [`Recorder::builder`]. Sampling stops when the recorder is dropped; any samples that
+/// have not been flushed are lost, so [`Recorder::stop`] should be used to save them first.
+#[derive(Debug)]
+pub struct Recorder {
+    shared: Arc<Shared>,
+    task: Option<TaskHandle>,
+}
+
+impl Recorder {
+    /// Creates a builder for a recorder that saves to the file at `path`.
+    ///
+    /// By default, the recorder samples every 10 milliseconds, keeps up to 1000 samples,
+    /// and flushes at the end of every competition period.
+    pub fn builder(path: &str) -> RecorderBuilder {
+        RecorderBuilder {
+            path: path.to_string(),
+            interval: Duration::from_millis(10),
+            capacity: 1000,
+            flush_on_period_end: true,
+            names: Vec::new(),
+            sources: Vec::new(),
+        }
+    }
+
+    /// Appends every buffered sample to the CSV file and clears the buffer.
+    ///
+    /// The header row is written first if the file doesn't exist yet. Sampling pauses while the file is written.
+    /// If writing fails, the samples are kept in the buffer.
+    pub fn flush(&self) -> Result<(), FsError> {
+        self.shared.flush()
+    }
+
+    /// Returns the number of samples in the buffer.
+    pub fn len(&self) -> usize {
+        self.shared.state().len
+    }
+
+    /// Returns `true` if there are no samples in the buffer.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of samples that were overwritten before they could be flushed because the buffer was full.
+    pub fn overwritten(&self) -> usize {
+        self.shared.state().overwritten
+    }
+
+    /// Stops sampling and flushes the remaining samples.
+    pub fn stop(mut self) -> Result<(), FsError> {
+        self.stop_task();
+        self.shared.flush()
+    }
+
+    fn stop_task(&mut self) {
+        self.shared.running.store(false, Ordering::Relaxed);
+        if let Some(task) = self.task.take() {
+            task.join();
+        }
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        self.stop_task();
+    }
+}
+
+struct Shared {
+    path: String,
+    running: AtomicBool,
+    state: Mutex<State>,
+}
+
+impl Shared {
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn flush(&self) -> Result<(), FsError> {
+        let mut state = self.state();
+        if state.len == 0 {
+            return Ok(());
+        }
+
+        let mut csv = String::new();
+        if !usd::exists(&self.path) {
+            state.write_header(&mut csv);
+        }
+        state.write_rows(&mut csv);
+
+        usd::append(&self.path, csv)?;
+        state.len = 0;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Shared {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Shared")
+            .field("path", &self.path)
+            .field("running", &self.running)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The ring buffer of samples.
+struct State {
+    names: Vec<String>,
+    sources: Vec<Source>,
+    /// The time of each sample, in milliseconds since the program started.
+    timestamps: Vec<u32>,
+    /// The values of each sample, one row of `sources.len()` values per timestamp.
+    /// Values that could not be read are stored as NaN.
+    values: Vec<f64>,
+    /// The index of the oldest sample.
+    start: usize,
+    len: usize,
+    overwritten: usize,
+}
+
+impl State {
+    fn sample(&mut self) {
+        let capacity = self.timestamps.len();
+        let index = (self.start + self.len) % capacity;
+        if self.len == capacity {
+            self.start = (self.start + 1) % capacity;
+            self.overwritten += 1;
+        } else {
+            self.len += 1;
+        }
+
+        self.timestamps[index] = unsafe { pros_sys::millis() };
+        let columns = self.sources.len();
+        for (value, source) in self.values[index * columns..(index + 1) * columns]
+            .iter_mut()
+            .zip(&mut self.sources)
+        {
+            *value = source().unwrap_or(f64::NAN);
+        }
+    }
+
+    fn write_header(&self, csv: &mut String) {
+        csv.push_str("time");
+        for name in &self.names {
+            if name.contains([',', '"', '\n', '\r']) {
+                _ = write!(csv, ",\"{}\"", name.replace('"', "\"\""));
+            } else {
+                _ = write!(csv, ",{name}");
+            }
+        }
+        csv.push('\n');
+    }
+
+    fn write_rows(&self, csv: &mut String) {
+        let capacity = self.timestamps.len();
+        let columns = self.sources.len();
+        for offset in 0..self.len {
+            let index = (self.start + offset) % capacity;
+            let timestamp = self.timestamps[index];
+            _ = write!(csv, "{}.{:03}", timestamp / 1000, timestamp % 1000);
+            for value in &self.values[index * columns..(index + 1) * columns] {
+                if value.is_nan() {
+                    csv.push(',');
+                } else {
+                    _ = write!(csv, ",{value}");
+                }
+            }
+            csv.push('\n');
+        }
+    }
+}
+
+/// A builder for a [`Recorder`], created with [`Recorder::builder`].
+#[must_use = "the recorder does not start until `start` is called"]
+pub struct RecorderBuilder {
+    path: String,
+    interval: Duration,
+    capacity: usize,
+    flush_on_period_end: bool,
+    names: Vec<String>,
+    sources: Vec<Source>,
+}
+
+impl RecorderBuilder {
+    /// Sets how often samples are taken.
+    pub const fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the number of samples that can be buffered before the oldest ones are overwritten.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        assert!(capacity > 0, "recorder capacity must be greater than zero");
+        self.capacity = capacity;
+        self
+    }
+
+    /// Sets whether the buffer is flushed automatically when the competition mode changes,
+    /// such as at the end of the autonomous period.
+    pub const fn flush_on_period_end(mut self, flush: bool) -> Self {
+        self.flush_on_period_end = flush;
+        self
+    }
+
+    /// Adds a column named `name` whose value is read by calling `source`.
+    ///
+    /// `source` can return either an `f64` or an `Option<f64>`, where `None` is recorded as an empty cell.
+    /// Fallible device readings can be converted with [`Result::ok`].
+    pub fn column<V: Into<Option<f64>>>(
+        mut self,
+        name: &str,
+        mut source: impl FnMut() -> V + Send + 'static,
+    ) -> Self {
+        self.names.push(name.to_string());
+        self.sources.push(Box::new(move || source().into()));
+        self
+    }
+
+    /// Starts sampling on a new task.
+    pub fn start(self) -> Recorder {
+        let columns = self.sources.len();
+        let shared = Arc::new(Shared {
+            path: self.path,
+            running: AtomicBool::new(true),
+            state: Mutex::new(State {
+                names: self.names,
+                sources: self.sources,
+                timestamps: vec![0; self.capacity],
+                values: vec![f64::NAN; self.capacity * columns],
+                start: 0,
+                len: 0,
+                overwritten: 0,
+            }),
+        });
+
+        let task = task::spawn({
+            let shared = shared.clone();
+            let interval = self.interval;
+            let flush_on_period_end = self.flush_on_period_end;
+            move || {
+                let mut timer = Interval::start();
+                let mut mode = competition::mode();
+                while shared.running.load(Ordering::Relaxed) {
+                    shared.state().sample();
+
+                    let new_mode = competition::mode();
+                    if flush_on_period_end && new_mode != mode {
+                        if let Err(err) = shared.flush() {
+                            pros_core::logger::warn!("failed to flush recorder: {err}");
+                        }
+                    }
+                    mode = new_mode;
+
+                    timer.delay(interval);
+                }
+            }
+        });
+
+        Recorder {
+            shared,
+            task: Some(task),
+        }
+    }
+}
+
+impl fmt::Debug for RecorderBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecorderBuilder")
+            .field("path", &self.path)
+            .field("interval", &self.interval)
+            .field("capacity", &self.capacity)
+            .field("flush_on_period_end", &self.flush_on_period_end)
+            .field("columns", &self.names)
+            .finish()
+    }
+}
@@ -5,7 +5,12 @@
 
 use alloc::{ffi::CString, string::String, vec::Vec};
 
-use pros_core::{bail_on, map_errno};
+use pros_core::{
+    bail_on,
+    logger::{LogEntry, Sink},
+    map_errno,
+    sync::{Mutex, PoisonError},
+};
 use pros_sys::PROS_ERR;
 use snafu::Snafu;
 
@@ -542,6 +547,33 @@ impl Screen {
     }
 }
 
+/// A [`Sink`] that prints log entries to the brain screen, scrolling as new entries are added.
+///
+/// Each entry is shown as its level followed by the message.
+#[derive(Debug)]
+pub struct ScreenSink {
+    screen: Mutex<Screen>,
+}
+
+impl ScreenSink {
+    /// Creates a sink that prints to the given screen.
+    pub fn new(screen: Screen) -> Self {
+        Self {
+            screen: Mutex::new(screen),
+        }
+    }
+}
+
+impl Sink for ScreenSink {
+    fn write(&self, entry: &LogEntry<'_>) {
+        let mut screen = self.screen.lock().unwrap_or_else(PoisonError::into_inner);
+        _ = core::fmt::Write::write_fmt(
+            &mut *screen,
+            format_args!("{:<5} {}\n", entry.level, entry.args),
+        );
+    }
+}
+
 #[derive(Debug, Snafu)]
 /// Errors that can occur when interacting with the screen.
 pub enum ScreenError {
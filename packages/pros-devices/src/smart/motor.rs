@@ -1,9 +1,20 @@
 //! V5 Smart Motors
 
-use core::time::Duration;
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
 
 use bitflags::bitflags;
-use pros_core::{bail_on, error::PortError, map_errno};
+use pros_core::{
+    bail_on,
+    error::PortError,
+    map_errno,
+    time::Instant,
+    timer::{self, TimerHandle},
+};
 use pros_sys::{PROS_ERR, PROS_ERR_F};
 use snafu::Snafu;
 
@@ -176,6 +187,36 @@ impl Motor {
         self.set_target(MotorControl::Position(position, velocity))
     }
 
+    /// Moves the motor to an absolute position, returning a future that completes once
+    /// the motor has settled at its target.
+    ///
+    /// The movement is started the first time the future is polled. The future resolves
+    /// once the motor is within [`MotorMoveFuture::tolerance`] of its target position and
+    /// its velocity has stayed below [`MotorMoveFuture::velocity_tolerance`] for the
+    /// [`MotorMoveFuture::settle_time`]. A timeout and stall detection can optionally be
+    /// configured on the returned future.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// motor
+    ///     .move_to(Position::from_rotations(2.0), 100)
+    ///     .timeout(Duration::from_secs(2))
+    ///     .await?;
+    /// ```
+    pub fn move_to(&mut self, position: Position, velocity: i32) -> MotorMoveFuture<'_> {
+        MotorMoveFuture::new(self, MotorMoveTarget::Absolute(position), velocity)
+    }
+
+    /// Moves the motor by an offset relative to its current position, returning a future that
+    /// completes once the motor has settled at its target.
+    ///
+    /// This behaves the same as [`Motor::move_to`], except that the target is calculated from
+    /// the position of the motor at the time the future is first polled.
+    pub fn move_by(&mut self, offset: Position, velocity: i32) -> MotorMoveFuture<'_> {
+        MotorMoveFuture::new(self, MotorMoveTarget::Relative(offset), velocity)
+    }
+
     /// Changes the output velocity for a profiled movement (motor_move_absolute or motor_move_relative).
     ///
     /// This will have no effect if the motor is not following a profiled movement.
@@ -683,12 +724,199 @@ impl From<MotorTuningConstants> for pros_sys::motor_pid_full_s_t {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MotorMoveTarget {
+    Absolute(Position),
+    Relative(Position),
+}
+
+/// Future that moves a motor to a position and waits for it to settle,
+/// created with [`Motor::move_to`] or [`Motor::move_by`].
+///
+/// If the future returns an error, the motor is left holding its position target.
+/// Dropping the future before it completes also leaves the target in place.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct MotorMoveFuture<'a> {
+    motor: &'a mut Motor,
+    target: MotorMoveTarget,
+    velocity: i32,
+    tolerance: Position,
+    velocity_tolerance: f64,
+    settle_time: Duration,
+    timeout: Option<Duration>,
+    stall_time: Option<Duration>,
+    /// The absolute target position and the time the movement started, once it has been started.
+    started: Option<(Position, Instant)>,
+    settled_since: Option<Instant>,
+    stalled_since: Option<Instant>,
+    /// Wakes the future for its next check, along with the waker it wakes.
+    timer: Option<(TimerHandle, Waker)>,
+}
+
+impl<'a> MotorMoveFuture<'a> {
+    /// The default distance from the target that the motor is considered to have reached it.
+    pub const DEFAULT_TOLERANCE: Position = Position::Degrees(5.0);
+
+    /// The default velocity (in RPM) under which the motor is considered stopped.
+    pub const DEFAULT_VELOCITY_TOLERANCE: f64 = 5.0;
+
+    /// The default amount of time the motor must remain settled for the movement to complete.
+    pub const DEFAULT_SETTLE_TIME: Duration = Duration::from_millis(50);
+
+    /// How often the motor is checked while the future is pending, which is how often motors report new data.
+    const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+    fn new(motor: &'a mut Motor, target: MotorMoveTarget, velocity: i32) -> Self {
+        Self {
+            motor,
+            target,
+            velocity,
+            tolerance: Self::DEFAULT_TOLERANCE,
+            velocity_tolerance: Self::DEFAULT_VELOCITY_TOLERANCE,
+            settle_time: Self::DEFAULT_SETTLE_TIME,
+            timeout: None,
+            stall_time: None,
+            started: None,
+            settled_since: None,
+            stalled_since: None,
+            timer: None,
+        }
+    }
+
+    /// Sets how close to the target the motor must be to be considered at the target.
+    pub const fn tolerance(mut self, tolerance: Position) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Sets the velocity (in RPM) under which the motor is considered stopped.
+    pub const fn velocity_tolerance(mut self, rpm: f64) -> Self {
+        self.velocity_tolerance = rpm;
+        self
+    }
+
+    /// Sets how long the motor must stay within tolerance before the movement completes.
+    pub const fn settle_time(mut self, settle_time: Duration) -> Self {
+        self.settle_time = settle_time;
+        self
+    }
+
+    /// Fails the movement with [`MotorError::MoveTimedOut`] if the motor has not settled
+    /// within the given duration of starting the movement.
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Fails the movement with [`MotorError::Stalled`] if the motor is stopped (its velocity is
+    /// below [`MotorMoveFuture::velocity_tolerance`]) or over current while outside of its
+    /// tolerance for longer than the given duration.
+    pub const fn stall_detection(mut self, stall_time: Duration) -> Self {
+        self.stall_time = Some(stall_time);
+        self
+    }
+
+    /// Starts the movement if it hasn't been started yet, then checks if the motor has settled.
+    fn update(&mut self) -> Result<bool, MotorError> {
+        let now = Instant::now();
+
+        let Some((target, started)) = self.started else {
+            let target = match self.target {
+                MotorMoveTarget::Absolute(position) => position,
+                MotorMoveTarget::Relative(offset) => self.motor.position()? + offset,
+            };
+            self.motor.set_position_target(target, self.velocity)?;
+            self.started = Some((target, now));
+            return Ok(false);
+        };
+
+        if let Some(timeout) = self.timeout {
+            if now - started > timeout {
+                return Err(MotorError::MoveTimedOut);
+            }
+        }
+
+        let error = (self.motor.position()? - target).into_degrees();
+        let tolerance = self.tolerance.into_degrees();
+
+        let in_range = -tolerance <= error && error <= tolerance;
+        let velocity = self.motor.velocity()?;
+        let stopped = -self.velocity_tolerance <= velocity && velocity <= self.velocity_tolerance;
+
+        if in_range && stopped {
+            let settled_since = *self.settled_since.get_or_insert(now);
+            if now - settled_since >= self.settle_time {
+                return Ok(true);
+            }
+        } else {
+            self.settled_since = None;
+        }
+
+        if let Some(stall_time) = self.stall_time {
+            if !in_range && (stopped || self.motor.is_over_current()?) {
+                let stalled_since = *self.stalled_since.get_or_insert(now);
+                if now - stalled_since >= stall_time {
+                    return Err(MotorError::Stalled);
+                }
+            } else {
+                self.stalled_since = None;
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+impl Future for MotorMoveFuture<'_> {
+    type Output = Result<(), MotorError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.update() {
+            Ok(true) => Poll::Ready(Ok(())),
+            Ok(false) => {
+                // The pending timer is replaced if it would wake a different task than the one polling now.
+                let reschedule = self.timer.as_ref().map_or(true, |(timer, waker)| {
+                    timer.is_finished() || !waker.will_wake(cx.waker())
+                });
+                if reschedule {
+                    if let Some((timer, _)) = &self.timer {
+                        timer.cancel();
+                    }
+                    let waker = cx.waker().clone();
+                    let timer = timer::after(Self::POLL_INTERVAL, {
+                        let waker = waker.clone();
+                        move || waker.wake()
+                    });
+                    self.timer = Some((timer, waker));
+                }
+                Poll::Pending
+            }
+            Err(err) => Poll::Ready(Err(err)),
+        }
+    }
+}
+
+impl Drop for MotorMoveFuture<'_> {
+    fn drop(&mut self) {
+        if let Some((timer, _)) = &self.timer {
+            timer.cancel();
+        }
+    }
+}
+
 #[derive(Debug, Snafu)]
 /// Errors that can occur when using a motor.
 pub enum MotorError {
     /// Failed to communicate with the motor while attempting to read flags.
     Busy,
 
+    /// The motor did not settle at its target before the movement timed out.
+    MoveTimedOut,
+
+    /// The motor stalled before reaching its target.
+    Stalled,
+
     /// This functionality is not currently implemented in hardware, even
     /// though the SDK may support it.
     NotImplemented,
@@ -0,0 +1,208 @@
+//! Generic serial communication over a smart port.
+//!
+//! A [`SerialPort`] can be used to talk to coprocessors and third-party sensors that use a serial protocol.
+//! It implements [`Read`](io::Read) and [`Write`](io::Write) from [`no_std_io`], which block until
+//! data can be read or written. Non-blocking reads and writes are available with [`SerialPort::try_read`]
+//! and [`SerialPort::try_write`].
+
+use core::time::Duration;
+
+use no_std_io::io;
+use pros_core::{bail_on, error::PortError, map_errno, task::delay};
+use pros_sys::PROS_ERR;
+use snafu::Snafu;
+
+use super::{SmartDevice, SmartDeviceType, SmartPort};
+
+/// How long to wait between checks for incoming data or free buffer space when blocking.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// A smart port configured as a generic serial port.
+#[derive(Debug, Eq, PartialEq)]
+pub struct SerialPort {
+    port: SmartPort,
+}
+
+impl SerialPort {
+    /// Configures a smart port as a serial port with the given baud rate.
+    pub fn open(port: SmartPort, baud_rate: u32) -> Result<Self, SerialError> {
+        unsafe {
+            bail_on!(PROS_ERR, pros_sys::serial_enable(port.index()));
+        }
+
+        let mut serial = Self { port };
+        serial.set_baud_rate(baud_rate)?;
+
+        Ok(serial)
+    }
+
+    /// Sets the baud rate of the serial port.
+    pub fn set_baud_rate(&mut self, baud_rate: u32) -> Result<(), SerialError> {
+        unsafe {
+            bail_on!(
+                PROS_ERR,
+                pros_sys::serial_set_baudrate(self.port.index(), baud_rate as i32)
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Clears the input and output buffers, discarding any data that has not been read or sent yet.
+    ///
+    /// Data in the output buffer is sent as soon as possible, so there is no need to flush it before it is sent.
+    pub fn clear_buffers(&mut self) -> Result<(), SerialError> {
+        unsafe {
+            bail_on!(PROS_ERR, pros_sys::serial_flush(self.port.index()));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of bytes that are available to be read from the input buffer.
+    pub fn bytes_to_read(&self) -> Result<usize, SerialError> {
+        Ok(
+            unsafe { bail_on!(PROS_ERR, pros_sys::serial_get_read_avail(self.port.index())) }
+                as usize,
+        )
+    }
+
+    /// Returns the number of bytes that can be written to the output buffer before it is full.
+    pub fn available_write_bytes(&self) -> Result<usize, SerialError> {
+        Ok(
+            unsafe { bail_on!(PROS_ERR, pros_sys::serial_get_write_free(self.port.index())) }
+                as usize,
+        )
+    }
+
+    /// Returns the next byte in the input buffer without removing it, or `None` if the buffer is empty.
+    pub fn peek_byte(&self) -> Result<Option<u8>, SerialError> {
+        let byte = unsafe { bail_on!(PROS_ERR, pros_sys::serial_peek_byte(self.port.index())) };
+        Ok(u8::try_from(byte).ok())
+    }
+
+    /// Reads the next byte from the input buffer, or returns `None` if the buffer is empty.
+    pub fn read_byte(&mut self) -> Result<Option<u8>, SerialError> {
+        let byte = unsafe { bail_on!(PROS_ERR, pros_sys::serial_read_byte(self.port.index())) };
+        Ok(u8::try_from(byte).ok())
+    }
+
+    /// Writes a single byte to the output buffer.
+    ///
+    /// Returns `false` if the output buffer was full.
+    pub fn write_byte(&mut self, byte: u8) -> Result<bool, SerialError> {
+        let written = unsafe {
+            bail_on!(
+                PROS_ERR,
+                pros_sys::serial_write_byte(self.port.index(), byte)
+            )
+        };
+        Ok(written > 0)
+    }
+
+    /// Reads as many bytes as are available into `buf`, without blocking.
+    ///
+    /// Returns the number of bytes read, which is zero if the input buffer is empty.
+    pub fn try_read(&mut self, buf: &mut [u8]) -> Result<usize, SerialError> {
+        let length = i32::try_from(buf.len()).unwrap_or(i32::MAX);
+        Ok(unsafe {
+            bail_on!(
+                PROS_ERR,
+                pros_sys::serial_read(self.port.index(), buf.as_mut_ptr(), length)
+            )
+        } as usize)
+    }
+
+    /// Writes as many bytes from `buf` as fit in the output buffer, without blocking.
+    ///
+    /// Returns the number of bytes written, which is zero if the output buffer is full.
+    pub fn try_write(&mut self, buf: &[u8]) -> Result<usize, SerialError> {
+        let length = i32::try_from(buf.len()).unwrap_or(i32::MAX);
+        // PROS doesn't modify the buffer, despite taking a mutable pointer.
+        Ok(unsafe {
+            bail_on!(
+                PROS_ERR,
+                pros_sys::serial_write(self.port.index(), buf.as_ptr().cast_mut(), length)
+            )
+        } as usize)
+    }
+}
+
+impl SmartDevice for SerialPort {
+    fn port_index(&self) -> u8 {
+        self.port.index()
+    }
+
+    fn device_type(&self) -> SmartDeviceType {
+        SmartDeviceType::Serial
+    }
+}
+
+impl io::Read for SerialPort {
+    /// Reads available bytes into `buf`, blocking until at least one byte is available.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            let read = self.try_read(buf).map_err(|_| {
+                io::Error::new(io::ErrorKind::Other, "failed to read from serial port")
+            })?;
+            if read > 0 {
+                return Ok(read);
+            }
+            delay(POLL_INTERVAL);
+        }
+    }
+}
+
+impl io::Write for SerialPort {
+    /// Writes as much of `buf` as fits in the output buffer, blocking until at least one byte can be written.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            let written = self.try_write(buf).map_err(|_| {
+                io::Error::new(io::ErrorKind::Other, "failed to write to serial port")
+            })?;
+            if written > 0 {
+                return Ok(written);
+            }
+            delay(POLL_INTERVAL);
+        }
+    }
+
+    /// Does nothing, since data in the output buffer is sent as soon as possible.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Snafu)]
+/// Errors that can occur when using a serial port.
+pub enum SerialError {
+    /// Another resource is currently trying to access the serial port.
+    ConcurrentAccess,
+    /// The given port is not a valid smart port.
+    InvalidPort,
+    /// A serious internal error occurred while writing to the serial port.
+    WriteFailed,
+    #[snafu(display("{source}"), context(false))]
+    /// Generic port related error
+    Port {
+        /// The source of the error
+        source: PortError,
+    },
+}
+
+map_errno! {
+    SerialError {
+        EACCES => Self::ConcurrentAccess,
+        EINVAL => Self::InvalidPort,
+        EIO => Self::WriteFailed,
+    }
+    inherit PortError;
+}
@@ -1,8 +1,371 @@
 //! USD api.
 //!
 //! The USD API provides functions for interacting with the SD card slot on the V5 Brain.
+//!
+//! Files can be read and written with a [`std::fs`](https://doc.rust-lang.org/std/fs/index.html)-like API.
+//! Paths are relative to the root of the SD card, so `autons/skills.txt` and `/usd/autons/skills.txt`
+//! refer to the same file.
+//!
+//! The V5 brain can only open files for either reading or writing, not both, and it cannot delete files.
+//! Data written to a file is only guaranteed to be saved once the [`File`] is dropped.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let path = usd::read_to_string("autons/skills.txt")?;
+//!
+//! let mut log = File::append("match.log")?;
+//! writeln!(log, "autonomous started")?;
+//! ```
+
+use alloc::{
+    ffi::CString,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use no_std_io::io;
+use pros_core::{bail_on, error::FromErrno};
+use snafu::Snafu;
+
+/// The initial size of the buffer used to list the files in a directory.
+const READ_DIR_BUFFER_SIZE: usize = 4096;
+
+/// The largest buffer used to list the files in a directory, which limits how long a listing can be.
+const MAX_READ_DIR_BUFFER_SIZE: usize = 64 * 1024;
 
 /// Checks if an SD card is installed.
 pub fn usd_installed() -> bool {
     unsafe { pros_sys::misc::usd_is_installed() == 1 }
 }
+
+/// Converts a path relative to the root of the SD card into an absolute path that PROS can open.
+fn absolute_path(path: &str) -> Result<CString, FsError> {
+    let relative = path
+        .strip_prefix("/usd")
+        .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+        .unwrap_or(path)
+        .trim_start_matches('/');
+
+    CString::new(format!("/usd/{relative}")).map_err(|_| FsError::InvalidInput)
+}
+
+/// An open file on the SD card.
+///
+/// The file is closed when it is dropped.
+#[derive(Debug)]
+pub struct File {
+    fd: i32,
+}
+
+impl File {
+    fn open_with(path: &str, flags: i32) -> Result<Self, FsError> {
+        let path = absolute_path(path)?;
+        let fd = unsafe { bail_on!(-1, pros_sys::open(path.as_ptr(), flags)) };
+        Ok(Self { fd })
+    }
+
+    /// Opens a file for reading.
+    pub fn open(path: &str) -> Result<Self, FsError> {
+        Self::open_with(path, pros_sys::O_RDONLY)
+    }
+
+    /// Opens a file for writing, creating it if it doesn't exist and truncating it if it does.
+    pub fn create(path: &str) -> Result<Self, FsError> {
+        Self::open_with(
+            path,
+            pros_sys::O_WRONLY | pros_sys::O_CREAT | pros_sys::O_TRUNC,
+        )
+    }
+
+    /// Opens a file for writing at the end of the file, creating it if it doesn't exist.
+    pub fn append(path: &str) -> Result<Self, FsError> {
+        Self::open_with(
+            path,
+            pros_sys::O_WRONLY | pros_sys::O_CREAT | pros_sys::O_APPEND,
+        )
+    }
+
+    fn read_raw(&mut self, buf: &mut [u8]) -> Result<usize, FsError> {
+        let read = unsafe {
+            bail_on!(
+                -1,
+                pros_sys::read(self.fd, buf.as_mut_ptr().cast(), buf.len())
+            )
+        };
+        Ok(read as usize)
+    }
+
+    fn write_raw(&mut self, buf: &[u8]) -> Result<usize, FsError> {
+        let written =
+            unsafe { bail_on!(-1, pros_sys::write(self.fd, buf.as_ptr().cast(), buf.len())) };
+        Ok(written as usize)
+    }
+
+    fn seek_raw(&mut self, offset: i32, whence: i32) -> Result<i32, FsError> {
+        Ok(unsafe { bail_on!(-1, pros_sys::lseek(self.fd, offset, whence)) })
+    }
+}
+
+impl io::Read for File {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        Ok(self.read_raw(buf)?)
+    }
+}
+
+impl io::Write for File {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(self.write_raw(buf)?)
+    }
+
+    /// Does nothing, since the V5 brain only guarantees that data is saved when the file is closed.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl io::Seek for File {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        // Offsets that don't fit in the `i32` taken by `lseek` are rejected rather than truncated.
+        let (offset, whence) = match pos {
+            io::SeekFrom::Start(offset) => (i32::try_from(offset), pros_sys::SEEK_SET),
+            io::SeekFrom::End(offset) => (i32::try_from(offset), pros_sys::SEEK_END),
+            io::SeekFrom::Current(offset) => (i32::try_from(offset), pros_sys::SEEK_CUR),
+        };
+        let offset = offset.map_err(|_| FsError::InvalidInput)?;
+
+        Ok(self.seek_raw(offset, whence)? as u64)
+    }
+}
+
+impl Drop for File {
+    fn drop(&mut self) {
+        unsafe {
+            pros_sys::close(self.fd);
+        }
+    }
+}
+
+/// An entry in a directory, returned by [`read_dir`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    directory: String,
+    name: String,
+}
+
+impl DirEntry {
+    /// The name of the file, without the directory it is in.
+    pub fn file_name(&self) -> &str {
+        &self.name
+    }
+
+    /// The path to the file, relative to the root of the SD card.
+    pub fn path(&self) -> String {
+        if self.directory.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}/{}", self.directory, self.name)
+        }
+    }
+}
+
+/// An iterator over the entries in a directory, returned by [`read_dir`].
+#[derive(Debug)]
+pub struct ReadDir {
+    entries: vec::IntoIter<DirEntry>,
+}
+
+impl Iterator for ReadDir {
+    type Item = DirEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next()
+    }
+}
+
+/// Returns an iterator over the files in a directory.
+///
+/// Only files are listed; subdirectories are not included.
+///
+/// # Errors
+///
+/// Returns [`FsError::Io`] if the names of the files in the directory take up more than 64 KiB.
+pub fn read_dir(path: &str) -> Result<ReadDir, FsError> {
+    let absolute = absolute_path(path)?;
+    // The VEX SDK expects paths relative to the root of the SD card.
+    let directory = absolute.to_str().unwrap()["/usd/".len()..]
+        .trim_end_matches('/')
+        .to_string();
+    let sdk_path = CString::new(format!("/{directory}")).unwrap();
+
+    let mut size = READ_DIR_BUFFER_SIZE;
+    let (buffer, len) = loop {
+        let mut buffer = vec![0u8; size];
+        let result = unsafe {
+            pros_sys::misc::vexFileDirectoryGet(
+                sdk_path.as_ptr(),
+                buffer.as_mut_ptr().cast(),
+                buffer.len() as u32,
+            )
+        };
+        match result {
+            0 => {}
+            // FR_NOT_READY
+            3 => return Err(FsError::NoCard),
+            // FR_NO_FILE, FR_NO_PATH
+            4 | 5 => return Err(FsError::NotFound),
+            _ => return Err(FsError::Io),
+        }
+
+        // The SDK cuts the listing off when it doesn't fit, possibly partway through a name,
+        // so a listing that fills the buffer is retried with a larger one.
+        match buffer.iter().position(|&b| b == 0) {
+            Some(len) if len < buffer.len() - 1 => break (buffer, len),
+            _ if size < MAX_READ_DIR_BUFFER_SIZE => size *= 2,
+            _ => return Err(FsError::Io),
+        }
+    };
+
+    let entries: Vec<_> = String::from_utf8_lossy(&buffer[..len])
+        .lines()
+        .filter(|name| !name.is_empty())
+        .map(|name| DirEntry {
+            directory: directory.clone(),
+            name: name.to_string(),
+        })
+        .collect();
+
+    Ok(ReadDir {
+        entries: entries.into_iter(),
+    })
+}
+
+/// Returns `true` if a file exists at `path`.
+pub fn exists(path: &str) -> bool {
+    File::open(path).is_ok()
+}
+
+/// Removes a file.
+///
+/// The VEX SDK does not currently support deleting files, so this returns [`FsError::Unsupported`]
+/// on current versions of PROS.
+pub fn remove(path: &str) -> Result<(), FsError> {
+    let path = absolute_path(path)?;
+    unsafe {
+        bail_on!(-1, pros_sys::unlink(path.as_ptr()));
+    }
+    Ok(())
+}
+
+/// Reads the entire contents of a file into a byte vector.
+pub fn read(path: &str) -> Result<Vec<u8>, FsError> {
+    let mut file = File::open(path)?;
+    let mut contents = Vec::new();
+    let mut chunk = [0; 512];
+    loop {
+        match file.read_raw(&mut chunk)? {
+            0 => return Ok(contents),
+            read => contents.extend_from_slice(&chunk[..read]),
+        }
+    }
+}
+
+/// Reads the entire contents of a file into a string.
+pub fn read_to_string(path: &str) -> Result<String, FsError> {
+    String::from_utf8(read(path)?).map_err(|_| FsError::InvalidData)
+}
+
+/// Writes `contents` to a file, creating it if it doesn't exist and replacing its contents if it does.
+pub fn write(path: &str, contents: impl AsRef<[u8]>) -> Result<(), FsError> {
+    write_all(&mut File::create(path)?, contents.as_ref())
+}
+
+/// Writes `contents` to the end of a file, creating it if it doesn't exist.
+pub fn append(path: &str, contents: impl AsRef<[u8]>) -> Result<(), FsError> {
+    write_all(&mut File::append(path)?, contents.as_ref())
+}
+
+fn write_all(file: &mut File, mut contents: &[u8]) -> Result<(), FsError> {
+    while !contents.is_empty() {
+        match file.write_raw(contents)? {
+            0 => return Err(FsError::NoSpace),
+            written => contents = &contents[written..],
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Snafu)]
+/// Errors that can occur when using the SD card.
+pub enum FsError {
+    /// No SD card is installed.
+    NoCard,
+    /// The file or directory was not found.
+    NotFound,
+    /// The file could not be accessed.
+    PermissionDenied,
+    /// The path is a directory.
+    IsADirectory,
+    /// The path or arguments are invalid.
+    InvalidInput,
+    /// The file does not contain valid UTF-8.
+    InvalidData,
+    /// Too many files are open at once.
+    TooManyOpenFiles,
+    /// The SD card is full.
+    NoSpace,
+    /// The SD card is read-only.
+    ReadOnly,
+    /// The file was not opened in a mode that supports the operation.
+    BadFile,
+    /// The operation is not supported by the V5 brain.
+    Unsupported,
+    /// An error occurred while reading or writing the SD card.
+    Io,
+}
+
+// This isn't implemented with `map_errno!`, since a failed SD card operation should never panic.
+// Unknown errno values, including 0, are reported as I/O errors instead.
+impl FromErrno for FsError {
+    fn from_errno(num: i32) -> Option<Self> {
+        use pros_sys::error::*;
+        Some(match num {
+            ENXIO => Self::NoCard,
+            ENOENT => Self::NotFound,
+            EACCES => Self::PermissionDenied,
+            EISDIR => Self::IsADirectory,
+            EINVAL => Self::InvalidInput,
+            ENFILE | EMFILE => Self::TooManyOpenFiles,
+            ENOSPC => Self::NoSpace,
+            EROFS => Self::ReadOnly,
+            EBADF => Self::BadFile,
+            ENOSYS => Self::Unsupported,
+            _ => Self::Io,
+        })
+    }
+}
+
+impl From<FsError> for io::Error {
+    fn from(err: FsError) -> Self {
+        let (kind, message) = match err {
+            FsError::NoCard => (io::ErrorKind::NotFound, "no SD card is installed"),
+            FsError::NotFound => (io::ErrorKind::NotFound, "file not found"),
+            FsError::PermissionDenied => (io::ErrorKind::PermissionDenied, "permission denied"),
+            FsError::IsADirectory => (io::ErrorKind::InvalidInput, "path is a directory"),
+            FsError::InvalidInput => (io::ErrorKind::InvalidInput, "invalid path or argument"),
+            FsError::InvalidData => (io::ErrorKind::InvalidData, "file is not valid UTF-8"),
+            FsError::TooManyOpenFiles => (io::ErrorKind::Other, "too many open files"),
+            FsError::NoSpace => (io::ErrorKind::Other, "SD card is full"),
+            FsError::ReadOnly => (io::ErrorKind::PermissionDenied, "SD card is read-only"),
+            FsError::BadFile => (
+                io::ErrorKind::PermissionDenied,
+                "file was not opened for this",
+            ),
+            FsError::Unsupported => (io::ErrorKind::Other, "operation is not supported"),
+            FsError::Io => (io::ErrorKind::Other, "SD card I/O error"),
+        };
+        io::Error::new(kind, message)
+    }
+}
@@ -1,6 +1,7 @@
 //! Panic handler implementation for [`pros-rs`](https://crates.io/crates/pros-rs).
 //! Supports printing a backtrace when running in the simulator.
 //! If the `display_panics` feature is enabled, it will also display the panic message on the V5 Brain display.
+//! If the `persist_panics` feature is enabled, panic reports can be saved to the SD card with the [`report`] module.
 
 #![no_std]
 
@@ -12,6 +13,9 @@ use pros_core::eprintln;
 #[cfg(feature = "display_panics")]
 use pros_devices::Screen;
 
+#[cfg(feature = "persist_panics")]
+pub mod report;
+
 #[cfg(target_arch = "wasm32")]
 extern "C" {
     /// Prints a backtrace to the debug console
@@ -95,6 +99,12 @@ pub fn panic(info: &core::panic::PanicInfo<'_>) -> ! {
 
     eprintln!("{msg}");
 
+    // Tasks spawned with a `JoinHandle` report the panic to it and are deleted instead of exiting the program.
+    pros_core::task::__handle_panic(msg.clone());
+
+    #[cfg(feature = "persist_panics")]
+    report::save(&task_name, info);
+
     unsafe {
         #[cfg(feature = "display_panics")]
         draw_error(&mut Screen::new(), &msg).unwrap_or_else(|err| {
@@ -0,0 +1,222 @@
+//! Panic reports saved to the SD card.
+//!
+//! Once [`enable`] has been called, every panic that ends the program appends a [`PanicReport`] to a file
+//! on the SD card, so crashes that happen on the field can be diagnosed after the brain is power-cycled.
+//! Panics in tasks spawned with a `JoinHandle` don't end the program, so they are not saved.
+//!
+//! The V5 brain cannot delete files, so instead of removing reports once they have been seen,
+//! [`take_unshown`] marks them as shown in the file.
+//!
+//! # Example
+//!
+//! ```ignore
+//! fn initialize() {
+//!     report::enable("panics.log");
+//!     if let Ok(Some(report)) = report::take_unshown() {
+//!         report::show(&report);
+//!     }
+//! }
+//! ```
+
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::{fmt, time::Duration};
+
+use pros_core::{eprintln, sync::OnceLock};
+use pros_devices::{
+    battery,
+    usd::{self, FsError},
+};
+
+const REPORT_START: &str = "=== panic ===";
+const REPORT_END: &str = "=== end ===";
+const REPORT_SHOWN: &str = "=== shown ===";
+
+static REPORT_PATH: OnceLock<String> = OnceLock::new();
+
+/// Starts saving panic reports to the file at `path` on the SD card.
+///
+/// Reports are appended, so the file keeps a history of every crash.
+/// This can only be called once; later calls are ignored.
+pub fn enable(path: &str) {
+    _ = REPORT_PATH.set(path.to_string());
+}
+
+/// The path that panic reports are saved to, if [`enable`] has been called.
+pub fn path() -> Option<&'static str> {
+    REPORT_PATH.get().map(String::as_str)
+}
+
+/// Information about a panic that ended the program.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PanicReport {
+    /// The name of the task that panicked.
+    pub task: String,
+    /// The panic message.
+    pub message: String,
+    /// The source location of the panic, like `src/main.rs:22:5`.
+    pub location: Option<String>,
+    /// The time between the start of the program and the panic.
+    pub uptime: Duration,
+    /// The battery charge at the time of the panic, as a percentage.
+    pub battery_capacity: Option<f64>,
+    /// The battery voltage at the time of the panic, in volts.
+    pub battery_voltage: Option<f64>,
+}
+
+impl PanicReport {
+    fn parse(block: &str) -> Option<Self> {
+        let mut report = Self {
+            task: String::new(),
+            message: String::new(),
+            location: None,
+            uptime: Duration::ZERO,
+            battery_capacity: None,
+            battery_voltage: None,
+        };
+
+        let mut lines = block.lines();
+        for line in lines.by_ref() {
+            let (key, value) = line.split_once(": ")?;
+            match key {
+                "task" => report.task = value.to_string(),
+                "location" => report.location = Some(value.to_string()),
+                "uptime" => {
+                    report.uptime =
+                        Duration::try_from_secs_f64(value.strip_suffix('s')?.parse().ok()?).ok()?
+                }
+                "battery" => {
+                    let (capacity, voltage) = value.split_once(' ')?;
+                    report.battery_capacity = capacity.strip_suffix('%')?.parse().ok();
+                    report.battery_voltage = voltage.strip_suffix('V')?.parse().ok();
+                }
+                "message" => {
+                    // The message is always last, and may span multiple lines.
+                    report.message = value.to_string();
+                    for line in lines.by_ref() {
+                        report.message.push('\n');
+                        report.message.push_str(line);
+                    }
+                    return Some(report);
+                }
+                _ => {}
+            }
+        }
+
+        None
+    }
+}
+
+impl fmt::Display for PanicReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{REPORT_START}")?;
+        writeln!(f, "task: {}", self.task)?;
+        if let Some(location) = &self.location {
+            writeln!(f, "location: {location}")?;
+        }
+        writeln!(f, "uptime: {:.3}s", self.uptime.as_secs_f64())?;
+        if let (Some(capacity), Some(voltage)) = (self.battery_capacity, self.battery_voltage) {
+            writeln!(f, "battery: {capacity:.0}% {voltage:.2}V")?;
+        }
+        writeln!(f, "message: {}", self.message)?;
+        writeln!(f, "{REPORT_END}")
+    }
+}
+
+/// Saves a report of the current panic, if [`enable`] has been called.
+pub(crate) fn save(task: &str, info: &core::panic::PanicInfo<'_>) {
+    let Some(path) = path() else {
+        return;
+    };
+
+    let location = info.location().map(ToString::to_string);
+    // `PanicInfo` only displays the message together with the location, so the location is stripped from it.
+    let full = format!("{info}");
+    let message = location
+        .as_ref()
+        .and_then(|location| full.strip_prefix(&format!("panicked at {location}:\n")))
+        .unwrap_or(&full)
+        .to_string();
+
+    let report = PanicReport {
+        task: task.to_string(),
+        message,
+        location,
+        uptime: Duration::from_millis(unsafe { pros_sys::millis() }.into()),
+        battery_capacity: battery::capacity().ok(),
+        battery_voltage: battery::voltage()
+            .ok()
+            .map(|millivolts| millivolts as f64 / 1000.0),
+    };
+
+    if let Err(err) = usd::append(path, report.to_string()) {
+        eprintln!("Failed to save panic report: {err}");
+    }
+}
+
+/// Returns the most recent panic report, or `None` if no panics have been saved.
+///
+/// Returns an error if [`enable`] has not been called, or if the file cannot be read.
+pub fn last() -> Result<Option<PanicReport>, FsError> {
+    Ok(read_last()?.map(|(report, _)| report))
+}
+
+/// Returns the most recent panic report if it has not been returned by this function before,
+/// and marks it as shown.
+///
+/// This is useful for showing a crash once, at the start of the next program.
+pub fn take_unshown() -> Result<Option<PanicReport>, FsError> {
+    match read_last()? {
+        Some((report, false)) => {
+            usd::append(
+                path().ok_or(FsError::InvalidInput)?,
+                format!("{REPORT_SHOWN}\n"),
+            )?;
+            Ok(Some(report))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Reads the most recent report, and whether it has been shown.
+fn read_last() -> Result<Option<(PanicReport, bool)>, FsError> {
+    let path = path().ok_or(FsError::InvalidInput)?;
+    let contents = match usd::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(FsError::NotFound) => return Ok(None),
+        Err(err) => return Err(err),
+    };
+
+    // Reports that were cut off by a power loss are skipped.
+    let Some(end) = contents.rfind(&format!("\n{REPORT_END}\n")) else {
+        return Ok(None);
+    };
+    let Some(start) = contents[..end].rfind(&format!("{REPORT_START}\n")) else {
+        return Ok(None);
+    };
+
+    let block = &contents[start + REPORT_START.len() + 1..end];
+    let shown = contents[end..].contains(REPORT_SHOWN);
+    Ok(PanicReport::parse(block).map(|report| (report, shown)))
+}
+
+/// Prints a report to stderr, and draws it on the screen if the `display_panics` feature is enabled.
+pub fn show(report: &PanicReport) {
+    let summary = format!(
+        "Last crash: task '{}' panicked at {} ({:.1}s after start):\n{}",
+        report.task,
+        report.location.as_deref().unwrap_or("<unknown>"),
+        report.uptime.as_secs_f64(),
+        report.message
+    );
+    eprintln!("{summary}");
+
+    #[cfg(feature = "display_panics")]
+    crate::draw_error(&mut unsafe { pros_devices::Screen::new() }, &summary).unwrap_or_else(
+        |err| {
+            eprintln!("Failed to draw panic report to screen: {err}");
+        },
+    );
+}
@@ -122,6 +122,23 @@ extern "C" {
      */
     pub fn task_abort_delay(task: task_t) -> bool;
     /**
+    Gets the minimum amount of stack space that has remained for a task since it
+    started executing. The closer this value is to zero, the closer the task has
+    come to overflowing its stack.
+
+    This is FreeRTOS's `uxTaskGetStackHighWaterMark`, which PROS does not wrap
+    or declare in its public headers, but which is exported by the kernel in
+    `libpros.a`. PROS task handles are FreeRTOS task handles, so any `task_t`
+    can be passed.
+
+    \param task
+           The task to check, or NULL for the current task
+
+    \return The smallest amount of free stack space the task has had, in words
+    (4 bytes each)
+     */
+    pub fn uxTaskGetStackHighWaterMark(task: task_t) -> u32;
+    /**
     Notify a task when a target task is being deleted.
 
     This function will configure the PROS kernel to call
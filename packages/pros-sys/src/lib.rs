@@ -38,14 +38,23 @@ pub use optical::*;
 pub use rotation::*;
 pub use rtos::*;
 pub use screen::*;
-#[cfg(feaute = "apix")]
 pub use serial::*;
 pub use vision::*;
-#[cfg(feaute = "apix")]
 pub mod serial;
 
 pub const CLOCKS_PER_SEC: u32 = 1000;
 
+pub const O_RDONLY: c_int = 0x0000;
+pub const O_WRONLY: c_int = 0x0001;
+pub const O_RDWR: c_int = 0x0002;
+pub const O_APPEND: c_int = 0x0008;
+pub const O_CREAT: c_int = 0x0200;
+pub const O_TRUNC: c_int = 0x0400;
+
+pub const SEEK_SET: c_int = 0;
+pub const SEEK_CUR: c_int = 1;
+pub const SEEK_END: c_int = 2;
+
 extern "C" {
     #[cfg(not(target_arch = "wasm32"))]
     pub fn memalign(alignment: usize, size: usize) -> *mut c_void;
@@ -56,6 +65,11 @@ extern "C" {
     pub fn puts(s: *const c_char) -> i32;
     pub fn exit(code: i32) -> !;
     pub fn write(fd: c_int, buf: *const c_void, count: usize) -> isize;
+    pub fn open(path: *const c_char, flags: c_int, ...) -> c_int;
+    pub fn close(fd: c_int) -> c_int;
+    pub fn read(fd: c_int, buf: *mut c_void, count: usize) -> isize;
+    pub fn lseek(fd: c_int, offset: i32, whence: c_int) -> i32;
+    pub fn unlink(path: *const c_char) -> c_int;
 
     fn initialize();
     fn opcontrol();
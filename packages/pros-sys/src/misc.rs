@@ -359,4 +359,22 @@ extern "C" {
     \return 1 if the SD card is installed, 0 otherwise
     */
     pub fn usd_is_installed() -> i32;
+    /**
+    Lists the files in a directory on the SD card, relative to the root of the card.
+
+    Puts the names of the files (not directories) in the directory into the buffer,
+    separated by newlines.
+
+    \note This is a VEX SDK function that is not wrapped by PROS 3.
+
+    \param path
+           The path to the directory, such as "/" or "/logs"
+    \param buffer
+           The buffer to put the file names in
+    \param len
+           The length of the buffer
+
+    \return FR_OK (0) if the operation was successful, or another FRESULT code otherwise.
+    */
+    pub fn vexFileDirectoryGet(path: *const c_char, buffer: *mut c_char, len: u32) -> u32;
 }
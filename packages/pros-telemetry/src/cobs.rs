@@ -0,0 +1,106 @@
+//! Consistent Overhead Byte Stuffing.
+//!
+//! COBS removes every zero byte from a packet, at a cost of at most one byte per 254 bytes of data,
+//! so that zero bytes can be used to mark where packets start and end.
+
+use alloc::vec::Vec;
+
+/// Appends the COBS encoding of `data` to `out`.
+///
+/// The encoded bytes never contain a zero byte.
+pub fn encode(data: &[u8], out: &mut Vec<u8>) {
+    let mut code_index = out.len();
+    let mut code = 1u8;
+    out.push(0);
+
+    for &byte in data {
+        if byte != 0 {
+            out.push(byte);
+            code += 1;
+        }
+
+        if byte == 0 || code == 0xFF {
+            out[code_index] = code;
+            code_index = out.len();
+            code = 1;
+            out.push(0);
+        }
+    }
+
+    out[code_index] = code;
+}
+
+/// Decodes a COBS-encoded packet, without its delimiters.
+///
+/// Returns `None` if `data` is not a valid COBS encoding.
+pub fn decode(data: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut index = 0;
+
+    while index < data.len() {
+        let code = data[index] as usize;
+        let block = data.get(index + 1..index + code)?;
+        if code == 0 || block.contains(&0) {
+            return None;
+        }
+
+        out.extend_from_slice(block);
+        index += code;
+        if code < 0xFF && index < data.len() {
+            out.push(0);
+        }
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::*;
+
+    fn encoded(data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        encode(data, &mut out);
+        out
+    }
+
+    #[test]
+    fn round_trip() {
+        let long: Vec<u8> = (0..1000).map(|i| (i % 7) as u8).collect();
+        let cases: [Vec<u8>; 8] = [
+            vec![],
+            vec![0],
+            vec![0, 0],
+            vec![1, 2, 3],
+            vec![1, 0, 2, 0],
+            vec![0xAB; 254],
+            vec![0xAB; 255],
+            long,
+        ];
+
+        for data in cases {
+            let encoded = encoded(&data);
+            assert!(!encoded.contains(&0), "encoding of {data:?} contains zero");
+            assert!(encoded.len() <= data.len() + data.len() / 254 + 1);
+            assert_eq!(decode(&encoded).as_deref(), Some(&data[..]));
+        }
+    }
+
+    #[test]
+    fn known_encodings() {
+        assert_eq!(encoded(&[]), [1]);
+        assert_eq!(encoded(&[0]), [1, 1]);
+        assert_eq!(encoded(&[0x11, 0x22, 0x00, 0x33]), [3, 0x11, 0x22, 2, 0x33]);
+    }
+
+    #[test]
+    fn rejects_invalid() {
+        // A code that points past the end of the packet.
+        assert_eq!(decode(&[5, 1, 2]), None);
+        // Zero bytes are never part of an encoding.
+        assert_eq!(decode(&[0, 1]), None);
+        assert_eq!(decode(&[3, 1, 0]), None);
+    }
+}
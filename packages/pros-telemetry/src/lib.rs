@@ -0,0 +1,372 @@
+//! Wire format for [`pros-rs`](https://crates.io/crates/pros) telemetry streams.
+//!
+//! Telemetry is sent by `pros_core::telemetry` as a stream of [`Frame`]s over the serial connection.
+//! Each frame is checksummed with CRC-16 and encoded with [COBS](cobs), then written between two zero bytes,
+//! so frames can be told apart from text printed to the same stream and a decoder that starts reading
+//! partway through a stream can find the start of the next frame.
+//!
+//! Channel values are sent as numeric IDs, so every channel is first registered with a [`Frame::Channel`]
+//! that gives it a name. The registrations are repeated periodically, so a stream can be decoded
+//! without seeing its start.
+//!
+//! This crate also contains the `pros-telemetry` command line tool, which converts a captured stream into CSV.
+//! It must be installed for the host rather than the V5 brain:
+//!
+//! ```sh
+//! cargo install --locked --path packages/pros-telemetry --features cli \
+//!     --target x86_64-unknown-linux-gnu --config 'unstable.build-std=["std", "panic_abort"]'
+//! pros-telemetry practice.bin > practice.csv
+//! ```
+//!
+//! The `--target` and `--config` flags override the workspace's V5 build settings; use the target triple
+//! of the host (shown by `rustc -vV`) on other platforms. The tests are run on the host the same way:
+//!
+//! ```sh
+//! cargo test -p pros-telemetry --lib --target x86_64-unknown-linux-gnu \
+//!     --config 'unstable.build-std=["std", "panic_unwind", "test"]'
+//! ```
+
+#![no_std]
+#![feature(error_in_core)]
+
+extern crate alloc;
+
+use alloc::{string::String, vec::Vec};
+
+use snafu::Snafu;
+
+pub mod cobs;
+
+/// The version of the wire format, sent in [`Frame::Hello`].
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// The longest encoded packet that [`Decoder`] accepts, in bytes.
+///
+/// This is enough for a sample of 256 channels.
+pub const MAX_PACKET_LEN: usize = 2048;
+
+const KIND_HELLO: u8 = 0;
+const KIND_CHANNEL: u8 = 1;
+const KIND_SAMPLE: u8 = 2;
+
+/// A single message in a telemetry stream.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Frame {
+    /// Sent when telemetry starts, and periodically after that.
+    Hello {
+        /// The [`PROTOCOL_VERSION`] of the sender.
+        version: u8,
+    },
+    /// Assigns a name to a channel ID.
+    Channel {
+        /// The ID used for the channel in samples.
+        id: u8,
+        /// The name of the channel.
+        name: String,
+    },
+    /// The values of some channels at a point in time.
+    Sample {
+        /// The time since the program started, in milliseconds.
+        timestamp: u32,
+        /// The ID and value of each channel that was updated since the last sample.
+        values: Vec<(u8, f32)>,
+    },
+}
+
+impl Frame {
+    /// Appends the encoded frame to `out`, including its delimiters.
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        let mut payload = Vec::new();
+        match self {
+            Self::Hello { version } => payload.extend_from_slice(&[KIND_HELLO, *version]),
+            Self::Channel { id, name } => {
+                payload.extend_from_slice(&[KIND_CHANNEL, *id]);
+                payload.extend_from_slice(name.as_bytes());
+            }
+            Self::Sample { timestamp, values } => {
+                payload.push(KIND_SAMPLE);
+                payload.extend_from_slice(&timestamp.to_le_bytes());
+                for (id, value) in values {
+                    payload.push(*id);
+                    payload.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+        }
+        payload.extend_from_slice(&crc16(&payload).to_le_bytes());
+
+        out.push(0);
+        cobs::encode(&payload, out);
+        out.push(0);
+    }
+
+    /// Decodes a frame from a COBS-encoded packet, without its delimiters.
+    pub fn decode(packet: &[u8]) -> Result<Self, DecodeError> {
+        let payload = cobs::decode(packet).ok_or(DecodeError::Cobs)?;
+        let Some((body, checksum)) = payload.split_last_chunk::<2>() else {
+            return Err(DecodeError::Truncated);
+        };
+        if crc16(body) != u16::from_le_bytes(*checksum) {
+            return Err(DecodeError::Checksum);
+        }
+
+        let (&kind, body) = body.split_first().ok_or(DecodeError::Truncated)?;
+        match kind {
+            KIND_HELLO => match body {
+                [version] => Ok(Self::Hello { version: *version }),
+                _ => Err(DecodeError::Truncated),
+            },
+            KIND_CHANNEL => {
+                let (&id, name) = body.split_first().ok_or(DecodeError::Truncated)?;
+                let name =
+                    String::from_utf8(name.to_vec()).map_err(|_| DecodeError::InvalidName)?;
+                Ok(Self::Channel { id, name })
+            }
+            KIND_SAMPLE => {
+                let (timestamp, values) = body
+                    .split_first_chunk::<4>()
+                    .ok_or(DecodeError::Truncated)?;
+                if values.len() % 5 != 0 {
+                    return Err(DecodeError::Truncated);
+                }
+
+                Ok(Self::Sample {
+                    timestamp: u32::from_le_bytes(*timestamp),
+                    values: values
+                        .chunks_exact(5)
+                        .map(|value| {
+                            (
+                                value[0],
+                                f32::from_le_bytes([value[1], value[2], value[3], value[4]]),
+                            )
+                        })
+                        .collect(),
+                })
+            }
+            kind => Err(DecodeError::UnknownKind { kind }),
+        }
+    }
+}
+
+/// A streaming decoder that splits a byte stream into [`Frame`]s.
+///
+/// Bytes between frames, such as text printed to the same stream, are ignored.
+#[derive(Debug, Default)]
+pub struct Decoder {
+    packet: Vec<u8>,
+    overflowed: bool,
+}
+
+impl Decoder {
+    /// Creates a decoder.
+    pub const fn new() -> Self {
+        Self {
+            packet: Vec::new(),
+            overflowed: false,
+        }
+    }
+
+    /// Feeds a byte to the decoder.
+    ///
+    /// Returns the decoded frame if `byte` ends one, or an error if the bytes before it were not a valid frame.
+    /// Errors are expected when text is mixed into the stream, and can usually be ignored.
+    pub fn push(&mut self, byte: u8) -> Option<Result<Frame, DecodeError>> {
+        if byte != 0 {
+            if self.packet.len() < MAX_PACKET_LEN {
+                self.packet.push(byte);
+            } else {
+                self.overflowed = true;
+            }
+            return None;
+        }
+
+        let result = match (self.packet.is_empty(), self.overflowed) {
+            (true, false) => None,
+            (_, true) => Some(Err(DecodeError::TooLong)),
+            (false, false) => Some(Frame::decode(&self.packet)),
+        };
+        self.packet.clear();
+        self.overflowed = false;
+        result
+    }
+}
+
+/// Computes the CRC-16/CCITT-FALSE checksum of `data`.
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[derive(Debug, Snafu)]
+/// Errors that can occur when decoding a [`Frame`].
+pub enum DecodeError {
+    /// The packet is not valid COBS.
+    Cobs,
+    /// The packet is too short for its frame kind.
+    Truncated,
+    /// The checksum of the packet does not match its contents.
+    Checksum,
+    /// The packet is longer than [`MAX_PACKET_LEN`].
+    TooLong,
+    /// The frame has an unknown kind: {kind}.
+    UnknownKind {
+        /// The kind byte of the frame.
+        kind: u8,
+    },
+    /// The channel name is not valid UTF-8.
+    InvalidName,
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec, vec::Vec};
+
+    use super::*;
+
+    fn frames() -> Vec<Frame> {
+        vec![
+            Frame::Hello {
+                version: PROTOCOL_VERSION,
+            },
+            Frame::Channel {
+                id: 3,
+                name: "left velocity".to_string(),
+            },
+            Frame::Sample {
+                timestamp: 123_456,
+                values: vec![(0, 1.5), (3, -0.25), (7, 0.0)],
+            },
+            Frame::Sample {
+                timestamp: 0,
+                values: vec![],
+            },
+        ]
+    }
+
+    fn encoded(frame: &Frame) -> Vec<u8> {
+        let mut out = Vec::new();
+        frame.encode(&mut out);
+        out
+    }
+
+    fn decode_stream(bytes: &[u8]) -> Vec<Result<Frame, DecodeError>> {
+        let mut decoder = Decoder::new();
+        bytes
+            .iter()
+            .filter_map(|&byte| decoder.push(byte))
+            .collect()
+    }
+
+    #[test]
+    fn crc16_check_value() {
+        assert_eq!(crc16(b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn frame_round_trip() {
+        for frame in frames() {
+            let encoded = encoded(&frame);
+            assert_eq!(encoded.first(), Some(&0));
+            assert_eq!(encoded.last(), Some(&0));
+            let packet = &encoded[1..encoded.len() - 1];
+            assert!(!packet.contains(&0));
+            assert_eq!(Frame::decode(packet).unwrap(), frame);
+        }
+    }
+
+    #[test]
+    fn detects_corruption() {
+        for frame in frames() {
+            let encoded = encoded(&frame);
+            let packet = &encoded[1..encoded.len() - 1];
+            for index in 0..packet.len() {
+                for flip in [0x01, 0x80, 0xFF] {
+                    let mut corrupted = packet.to_vec();
+                    corrupted[index] ^= flip;
+                    if corrupted[index] == 0 {
+                        continue;
+                    }
+                    assert!(
+                        Frame::decode(&corrupted).is_err(),
+                        "corrupting byte {index} of {frame:?} was not detected"
+                    );
+                }
+            }
+        }
+
+        let encoded = encoded(&frames()[2]);
+        let truncated = &encoded[1..encoded.len() - 2];
+        assert!(Frame::decode(truncated).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_kind() {
+        let mut payload = vec![42, 1, 2];
+        payload.extend_from_slice(&crc16(&payload).to_le_bytes());
+        let mut packet = Vec::new();
+        cobs::encode(&payload, &mut packet);
+        assert!(matches!(
+            Frame::decode(&packet),
+            Err(DecodeError::UnknownKind { kind: 42 })
+        ));
+    }
+
+    #[test]
+    fn decodes_stream() {
+        let mut stream = Vec::new();
+        for frame in frames() {
+            frame.encode(&mut stream);
+        }
+
+        let decoded: Vec<Frame> = decode_stream(&stream)
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(decoded, frames());
+    }
+
+    #[test]
+    fn skips_text_between_frames() {
+        let mut stream = b"Hello from the brain!\n".to_vec();
+        frames()[1].encode(&mut stream);
+        stream.extend_from_slice(b"more text\n");
+        frames()[2].encode(&mut stream);
+
+        let decoded: Vec<Frame> = decode_stream(&stream).into_iter().flatten().collect();
+        assert_eq!(decoded, frames()[1..3]);
+    }
+
+    #[test]
+    fn resyncs_mid_stream() {
+        let mut stream = Vec::new();
+        for frame in frames() {
+            frame.encode(&mut stream);
+        }
+
+        // Start reading partway through the first frame, as if the capture started late.
+        let mut decoded = decode_stream(&stream[3..]).into_iter();
+        assert!(decoded.next().unwrap().is_err());
+        let rest: Vec<Frame> = decoded.map(Result::unwrap).collect();
+        assert_eq!(rest, frames()[1..]);
+    }
+
+    #[test]
+    fn recovers_from_overlong_packets() {
+        let mut stream = vec![0xAA; MAX_PACKET_LEN + 10];
+        frames()[0].encode(&mut stream);
+
+        let decoded = decode_stream(&stream);
+        assert!(matches!(decoded[0], Err(DecodeError::TooLong)));
+        assert_eq!(decoded[1].as_ref().unwrap(), &frames()[0]);
+        assert_eq!(decoded.len(), 2);
+    }
+}
@@ -0,0 +1,154 @@
+//! Converts a captured telemetry stream into CSV.
+//!
+//! Usage: `pros-telemetry [INPUT]`. The stream is read from `INPUT`, or from stdin if it is omitted or `-`,
+//! and the CSV is written to stdout. The first column is the time in seconds, followed by one column for
+//! every channel in the order they were registered. Cells are left empty for channels that were not
+//! updated in a sample.
+
+use std::{
+    collections::BTreeMap,
+    env,
+    fs::File,
+    io::{self, BufWriter, Read, Write},
+    process::ExitCode,
+};
+
+use pros_telemetry::{Decoder, Frame, PROTOCOL_VERSION};
+
+/// The samples decoded from a stream.
+#[derive(Debug, Default)]
+struct Table {
+    /// The name of every column after the time column.
+    columns: Vec<String>,
+    /// The column that each channel ID currently refers to.
+    ids: BTreeMap<u8, usize>,
+    /// The timestamp and values of each sample.
+    rows: Vec<(u32, Vec<(usize, f32)>)>,
+    /// The number of values from channels that were never registered.
+    skipped: usize,
+}
+
+impl Table {
+    fn push(&mut self, frame: Frame) {
+        match frame {
+            // The program was restarted, so the channel IDs may be reused for different channels.
+            Frame::Hello { .. } => self.ids.clear(),
+            Frame::Channel { id, name } => {
+                let column = match self.columns.iter().position(|column| *column == name) {
+                    Some(column) => column,
+                    None => {
+                        self.columns.push(name);
+                        self.columns.len() - 1
+                    }
+                };
+                self.ids.insert(id, column);
+            }
+            Frame::Sample { timestamp, values } => {
+                let row = values
+                    .into_iter()
+                    .filter_map(|(id, value)| {
+                        let column = self.ids.get(&id).copied();
+                        if column.is_none() {
+                            self.skipped += 1;
+                        }
+                        column.map(|column| (column, value))
+                    })
+                    .collect();
+                self.rows.push((timestamp, row));
+            }
+        }
+    }
+
+    fn write_csv(&self, out: &mut impl Write) -> io::Result<()> {
+        write!(out, "time")?;
+        for column in &self.columns {
+            write!(out, ",{}", escape(column))?;
+        }
+        writeln!(out)?;
+
+        let mut cells = vec![None; self.columns.len()];
+        for (timestamp, values) in &self.rows {
+            cells.fill(None);
+            for &(column, value) in values {
+                cells[column] = Some(value);
+            }
+
+            write!(out, "{}.{:03}", timestamp / 1000, timestamp % 1000)?;
+            for cell in &cells {
+                match cell {
+                    Some(value) => write!(out, ",{value}")?,
+                    None => write!(out, ",")?,
+                }
+            }
+            writeln!(out)?;
+        }
+
+        out.flush()
+    }
+}
+
+/// Quotes a CSV field if necessary.
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn run() -> io::Result<()> {
+    let mut input: Box<dyn Read> = match env::args().nth(1).as_deref() {
+        None | Some("-") => Box::new(io::stdin().lock()),
+        Some(path) => Box::new(File::open(path)?),
+    };
+    let mut bytes = Vec::new();
+    input.read_to_end(&mut bytes)?;
+
+    let mut decoder = Decoder::new();
+    let mut table = Table::default();
+    let mut invalid_frames = 0;
+    for byte in bytes {
+        match decoder.push(byte) {
+            Some(Ok(Frame::Hello { version })) if version != PROTOCOL_VERSION => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unsupported protocol version {version}, expected {PROTOCOL_VERSION}"),
+                ));
+            }
+            Some(Ok(frame)) => table.push(frame),
+            Some(Err(_)) => invalid_frames += 1,
+            None => {}
+        }
+    }
+
+    table.write_csv(&mut BufWriter::new(io::stdout().lock()))?;
+
+    eprintln!(
+        "decoded {} samples of {} channels",
+        table.rows.len(),
+        table.columns.len()
+    );
+    if invalid_frames > 0 {
+        eprintln!(
+            "ignored {invalid_frames} packets that were not valid frames, such as printed text"
+        );
+    }
+    if table.skipped > 0 {
+        eprintln!(
+            "skipped {} values from channels that were never registered",
+            table.skipped
+        );
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
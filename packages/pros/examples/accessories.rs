@@ -51,7 +51,7 @@ impl AsyncRobot for ExampleRobot {
             let motor = Arc::clone(&self.motor); // Obtain a shared reference to our motor to safely share between tasks.
 
             move || loop {
-                println!("Motor stopped? {}", motor.lock().velocity() < 2);
+                println!("Motor stopped? {}", motor.lock().unwrap().velocity() < 2);
 
                 // Sleep the task as to not steal processing time from the OS.
                 // This should always be done in any loop, including loops in the main task.
@@ -66,6 +66,7 @@ impl AsyncRobot for ExampleRobot {
             // Set output takes a float from -1 to 1 that is scaled to -12 to 12 volts.
             self.motor
                 .lock()
+                .unwrap()
                 .set_voltage(Motor::MAX_VOLTAGE * controller.state()?.joysticks.right.y)?;
 
             // println!("pid out {}", pid.update(10.0, motor.position().into_degrees() as f32));
@@ -96,7 +96,7 @@ pub mod prelude {
             AdiDevice, AdiPort,
         },
         color::Rgb,
-        controller::Controller,
+        controller::{Controller, ControllerInput},
         peripherals::{DynamicPeripherals, Peripherals},
         position::Position,
         screen::{Circle, Line, Rect, Screen, Text, TextFormat, TextPosition, TouchState},
@@ -109,6 +109,7 @@ pub mod prelude {
             motor::{BrakeMode, Direction, Gearset, Motor, MotorControl},
             optical::OpticalSensor,
             rotation::RotationSensor,
+            serial::SerialPort,
             vision::VisionSensor,
             SmartDevice, SmartPort,
         },